@@ -0,0 +1,533 @@
+use hickory_resolver::proto::op::{Message, MessageType, OpCode, Query};
+use hickory_resolver::proto::rr::{Name, Record, RecordType};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DNS_PORT: u16 = 53;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsTransport {
+    #[default]
+    Udp,
+    Tcp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DnsQuery {
+    pub name: String,
+    /// A, AAAA, CNAME, MX, TXT, NS, SOA, SRV, CAA, PTR, ... Defaults to A.
+    pub record_type: Option<String>,
+    /// "8.8.8.8", "1.1.1.1:53", "[2606:4700::1111]:53" or a host name.
+    /// Defaults to the first nameserver of the system configuration.
+    pub nameserver: Option<String>,
+    #[serde(default)]
+    pub transport: DnsTransport,
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DnsRecord {
+    pub name: String,
+    pub record_type: String,
+    pub ttl: u32,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DnsResponse {
+    pub query_name: String,
+    pub record_type: String,
+    pub nameserver: String,
+    pub transport: DnsTransport,
+    /// Numeric RCODE, e.g. 0 for NOERROR, 3 for NXDOMAIN.
+    pub response_code: u16,
+    pub response_code_name: String,
+    pub authoritative: bool,
+    pub truncated: bool,
+    pub recursion_available: bool,
+    pub latency_ms: f64,
+    pub answers: Vec<DnsRecord>,
+    pub authority: Vec<DnsRecord>,
+    pub additional: Vec<DnsRecord>,
+}
+
+fn system_nameserver() -> Result<SocketAddr, String> {
+    let (config, _opts) = hickory_resolver::system_conf::read_system_conf()
+        .map_err(|e| format!("Failed to read system DNS configuration: {}", e))?;
+    config
+        .name_servers()
+        .first()
+        .map(|ns| ns.socket_addr)
+        .ok_or_else(|| "No nameserver configured on this system".to_string())
+}
+
+fn parse_nameserver(nameserver: Option<&str>) -> Result<SocketAddr, String> {
+    let Some(ns) = nameserver.map(str::trim).filter(|s| !s.is_empty()) else {
+        return system_nameserver();
+    };
+    if let Ok(addr) = ns.parse::<SocketAddr>() {
+        return Ok(addr);
+    }
+    if let Ok(ip) = ns.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, DNS_PORT));
+    }
+    // A host name, optionally with a port
+    let resolved = match ns.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host, port.parse::<u16>().unwrap_or(DNS_PORT)).to_socket_addrs(),
+        _ => (ns, DNS_PORT).to_socket_addrs(),
+    };
+    resolved
+        .map_err(|e| format!("Invalid nameserver '{}': {}", ns, e))?
+        .next()
+        .ok_or_else(|| format!("Invalid nameserver '{}'", ns))
+}
+
+/// Turns user input into the name to query. IP addresses become their reverse
+/// (in-addr.arpa / ip6.arpa) name for PTR lookups.
+fn query_name(name: &str, record_type: RecordType) -> Result<Name, String> {
+    let name = name.trim();
+    if record_type == RecordType::PTR {
+        if let Ok(ip) = name.parse::<IpAddr>() {
+            return Ok(Name::from(ip));
+        }
+    }
+    let fqdn = if name.ends_with('.') { name.to_string() } else { format!("{}.", name) };
+    Name::from_utf8(&fqdn).map_err(|e| format!("Invalid domain name '{}': {}", name, e))
+}
+
+fn to_records(records: &[Record]) -> Vec<DnsRecord> {
+    records
+        .iter()
+        .map(|r| DnsRecord {
+            name: r.name().to_string(),
+            record_type: r.record_type().to_string(),
+            ttl: r.ttl(),
+            data: r.data().map(|d| d.to_string()).unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn exchange_udp(server: SocketAddr, request: &[u8], id: u16, timeout: Duration) -> Result<Message, String> {
+    let bind: SocketAddr = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }.parse().unwrap();
+    let socket = UdpSocket::bind(bind).map_err(|e| e.to_string())?;
+    socket.connect(server).map_err(|e| e.to_string())?;
+    socket.send(request).map_err(|e| e.to_string())?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buf = [0u8; 4096];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(format!("Timed out waiting for {}", server));
+        }
+        socket.set_read_timeout(Some(remaining)).map_err(|e| e.to_string())?;
+        let n = socket.recv(&mut buf).map_err(|e| match e.kind() {
+            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => format!("Timed out waiting for {}", server),
+            _ => e.to_string(),
+        })?;
+        // Ignore stray or spoofed datagrams that don't answer our query
+        if let Ok(message) = Message::from_vec(&buf[..n]) {
+            if message.id() == id {
+                return Ok(message);
+            }
+        }
+    }
+}
+
+fn exchange_tcp(server: SocketAddr, request: &[u8], id: u16, timeout: Duration) -> Result<Message, String> {
+    let map_err = |e: std::io::Error| match e.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => format!("Timed out waiting for {}", server),
+        _ => e.to_string(),
+    };
+    // A zero timeout means "no timeout" to the socket calls below
+    if timeout.is_zero() {
+        return Err(format!("Timed out waiting for {}", server));
+    }
+    let mut stream = TcpStream::connect_timeout(&server, timeout).map_err(map_err)?;
+    stream.set_read_timeout(Some(timeout)).map_err(map_err)?;
+    stream.set_write_timeout(Some(timeout)).map_err(map_err)?;
+
+    // DNS over TCP prefixes every message with its 16-bit length
+    let mut framed = (request.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(request);
+    stream.write_all(&framed).map_err(map_err)?;
+
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).map_err(map_err)?;
+    let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut buf).map_err(map_err)?;
+    let message = Message::from_vec(&buf).map_err(|e| format!("Malformed response from {}: {}", server, e))?;
+    // The connection carries only our query, so a different id is a broken server, not noise
+    if message.id() != id {
+        return Err(format!("Response from {} does not match the query id", server));
+    }
+    Ok(message)
+}
+
+pub(crate) fn run_query(query: &DnsQuery) -> Result<DnsResponse, String> {
+    let record_type = match query.record_type.as_deref().map(str::trim) {
+        None | Some("") => RecordType::A,
+        Some(t) => RecordType::from_str(&t.to_ascii_uppercase()).map_err(|_| format!("Unknown record type '{}'", t))?,
+    };
+    let name = query_name(&query.name, record_type)?;
+    let server = parse_nameserver(query.nameserver.as_deref())?;
+    let timeout = query.timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_TIMEOUT);
+
+    let id = uuid::Uuid::new_v4().as_u128() as u16;
+    let mut message = Message::new();
+    message
+        .set_id(id)
+        .set_message_type(MessageType::Query)
+        .set_op_code(OpCode::Query)
+        .set_recursion_desired(true)
+        .add_query(Query::query(name.clone(), record_type));
+    let request = message.to_vec().map_err(|e| e.to_string())?;
+
+    let started = Instant::now();
+    let mut transport = query.transport;
+    let mut response = match transport {
+        DnsTransport::Udp => exchange_udp(server, &request, id, timeout)?,
+        DnsTransport::Tcp => exchange_tcp(server, &request, id, timeout)?,
+    };
+    // The answer did not fit in a datagram; ask again over TCP within the same timeout
+    if transport == DnsTransport::Udp && response.truncated() {
+        transport = DnsTransport::Tcp;
+        response = exchange_tcp(server, &request, id, timeout.saturating_sub(started.elapsed()))?;
+    }
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let rcode = response.response_code();
+    Ok(DnsResponse {
+        query_name: name.to_string(),
+        record_type: record_type.to_string(),
+        nameserver: server.to_string(),
+        transport,
+        response_code: u16::from(rcode),
+        response_code_name: rcode.to_str().to_string(),
+        authoritative: response.authoritative(),
+        truncated: response.truncated(),
+        recursion_available: response.recursion_available(),
+        latency_ms,
+        answers: to_records(response.answers()),
+        authority: to_records(response.name_servers()),
+        additional: to_records(response.additionals()),
+    })
+}
+
+#[tauri::command]
+pub async fn dns_query(query: DnsQuery) -> Result<DnsResponse, String> {
+    tauri::async_runtime::spawn_blocking(move || run_query(&query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn resolve_domain(domain: &str) -> Result<Vec<String>, String> {
+    let domain = domain.trim();
+    // IP literals (including "[::1]:8080" or "1.2.3.4:80") need no lookup
+    if let Ok(addr) = domain.parse::<SocketAddr>() {
+        return Ok(vec![addr.ip().to_string()]);
+    }
+    if let Ok(ip) = domain.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        return Ok(vec![ip.to_string()]);
+    }
+
+    match (domain, 0).to_socket_addrs() {
+        Ok(addrs) => {
+            let mut ips: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
+            // Simple dedup
+            ips.sort();
+            ips.dedup();
+            Ok(ips)
+        },
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+const DEFAULT_PARALLELISM: usize = 8;
+const MAX_PARALLELISM: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LookupKind {
+    Forward,
+    Reverse,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BulkLookupOptions {
+    /// Number of lookups in flight at once. Defaults to 8.
+    pub parallelism: Option<usize>,
+    /// Per-query timeout.
+    pub timeout_ms: Option<u64>,
+    pub nameserver: Option<String>,
+    #[serde(default)]
+    pub transport: DnsTransport,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkLookupResult {
+    pub input: String,
+    pub kind: LookupKind,
+    /// IP addresses for forward lookups, host names for reverse lookups.
+    pub results: Vec<String>,
+    pub error: Option<String>,
+}
+
+fn answers_of(
+    name: &str,
+    record_type: &str,
+    options: &BulkLookupOptions,
+) -> Result<Vec<String>, String> {
+    let response = run_query(&DnsQuery {
+        name: name.to_string(),
+        record_type: Some(record_type.to_string()),
+        nameserver: options.nameserver.clone(),
+        transport: options.transport,
+        timeout_ms: options.timeout_ms,
+    })?;
+    if response.response_code != 0 {
+        return Err(response.response_code_name);
+    }
+    // Skip CNAMEs and other records that only lead to the answer
+    Ok(response
+        .answers
+        .into_iter()
+        .filter(|r| r.record_type == record_type)
+        .map(|r| r.data.trim_end_matches('.').to_string())
+        .collect())
+}
+
+/// PTR names for `ip`, without the trailing dot.
+fn reverse(ip: IpAddr, options: &BulkLookupOptions) -> Result<Vec<String>, String> {
+    answers_of(&ip.to_string(), "PTR", options)
+}
+
+/// A and AAAA addresses for `name`; fails only if both queries fail.
+fn forward(name: &str, options: &BulkLookupOptions) -> Result<Vec<String>, String> {
+    let v4 = answers_of(name, "A", options);
+    let v6 = answers_of(name, "AAAA", options);
+    match (v4, v6) {
+        (Err(e), Err(_)) => Err(e),
+        (v4, v6) => Ok(v4.unwrap_or_default().into_iter().chain(v6.unwrap_or_default()).collect()),
+    }
+}
+
+fn lookup_entry(input: &str, options: &BulkLookupOptions) -> BulkLookupResult {
+    let trimmed = input.trim();
+    let (kind, result) = match trimmed.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) => (LookupKind::Reverse, reverse(ip, options)),
+        Err(_) => (LookupKind::Forward, forward(trimmed, options)),
+    };
+    match result {
+        Ok(results) => BulkLookupResult { input: input.to_string(), kind, results, error: None },
+        Err(e) => BulkLookupResult { input: input.to_string(), kind, results: Vec::new(), error: Some(e) },
+    }
+}
+
+pub(crate) fn bulk_lookup(entries: Vec<String>, options: BulkLookupOptions) -> Vec<BulkLookupResult> {
+    let parallelism = options
+        .parallelism
+        .unwrap_or(DEFAULT_PARALLELISM)
+        .clamp(1, MAX_PARALLELISM)
+        .min(entries.len().max(1));
+
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let results: std::sync::Mutex<Vec<Option<BulkLookupResult>>> = std::sync::Mutex::new(vec![None; entries.len()]);
+
+    // Each worker pulls the next unclaimed entry; results keep the input order
+    std::thread::scope(|scope| {
+        for _ in 0..parallelism {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let Some(entry) = entries.get(i) else { break };
+                let result = lookup_entry(entry, &options);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+#[tauri::command]
+pub async fn reverse_lookup(ip: String, nameserver: Option<String>) -> Result<Vec<String>, String> {
+    let addr = ip
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("Invalid IP address '{}'", ip))?;
+    let options = BulkLookupOptions { nameserver, ..Default::default() };
+    tauri::async_runtime::spawn_blocking(move || reverse(addr, &options))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Resolves host names (A/AAAA) and IP addresses (PTR) concurrently. Failures are
+/// reported per entry instead of failing the whole batch.
+#[tauri::command]
+pub async fn dns_bulk_lookup(
+    entries: Vec<String>,
+    options: Option<BulkLookupOptions>,
+) -> Result<Vec<BulkLookupResult>, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || bulk_lookup(entries, options))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_resolver::proto::op::ResponseCode;
+    use hickory_resolver::proto::rr::rdata::A;
+    use hickory_resolver::proto::rr::RData;
+    use std::net::TcpListener;
+    use std::thread;
+
+    type Responder = fn(&Message) -> Message;
+
+    fn reply(request: &Message, rcode: ResponseCode) -> Message {
+        let mut response = Message::new();
+        response
+            .set_id(request.id())
+            .set_message_type(MessageType::Response)
+            .set_op_code(OpCode::Query)
+            .set_recursion_available(true)
+            .set_response_code(rcode)
+            .add_queries(request.queries().to_vec());
+        response
+    }
+
+    fn answer(request: &Message) -> Message {
+        let mut response = reply(request, ResponseCode::NoError);
+        let name = request.queries()[0].name().clone();
+        response.add_answer(Record::from_rdata(name, 300, RData::A(A::new(192, 0, 2, 1))));
+        response
+    }
+
+    fn nxdomain(request: &Message) -> Message {
+        reply(request, ResponseCode::NXDomain)
+    }
+
+    fn truncated(request: &Message) -> Message {
+        let mut response = reply(request, ResponseCode::NoError);
+        response.set_truncated(true);
+        response
+    }
+
+    fn wrong_id(request: &Message) -> Message {
+        let mut response = answer(request);
+        response.set_id(request.id().wrapping_add(1));
+        response
+    }
+
+    /// Answers one query on `socket`.
+    fn serve_udp(socket: UdpSocket, respond: Responder) {
+        thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            let (n, peer) = socket.recv_from(&mut buf).unwrap();
+            let request = Message::from_vec(&buf[..n]).unwrap();
+            socket.send_to(&respond(&request).to_vec().unwrap(), peer).unwrap();
+        });
+    }
+
+    /// Answers one query on `listener`, framed as DNS over TCP.
+    fn serve_tcp(listener: TcpListener, respond: Responder) {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut len = [0u8; 2];
+            stream.read_exact(&mut len).unwrap();
+            let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+            stream.read_exact(&mut buf).unwrap();
+            let response = respond(&Message::from_vec(&buf).unwrap()).to_vec().unwrap();
+            stream.write_all(&(response.len() as u16).to_be_bytes()).unwrap();
+            stream.write_all(&response).unwrap();
+        });
+    }
+
+    fn udp_stub(respond: Responder) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        serve_udp(socket, respond);
+        addr
+    }
+
+    fn tcp_stub(respond: Responder) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        serve_tcp(listener, respond);
+        addr
+    }
+
+    fn query(server: SocketAddr, transport: DnsTransport, timeout_ms: u64) -> Result<DnsResponse, String> {
+        run_query(&DnsQuery {
+            name: "host.test".to_string(),
+            record_type: None,
+            nameserver: Some(server.to_string()),
+            transport,
+            timeout_ms: Some(timeout_ms),
+        })
+    }
+
+    fn addresses(response: &DnsResponse) -> Vec<&str> {
+        response.answers.iter().map(|r| r.data.as_str()).collect()
+    }
+
+    #[test]
+    fn udp_answer() {
+        let response = query(udp_stub(answer), DnsTransport::Udp, 2000).unwrap();
+        assert_eq!(response.transport, DnsTransport::Udp);
+        assert_eq!(response.response_code, 0);
+        assert_eq!(response.query_name, "host.test.");
+        assert_eq!(addresses(&response), ["192.0.2.1"]);
+    }
+
+    #[test]
+    fn tcp_answer() {
+        let response = query(tcp_stub(answer), DnsTransport::Tcp, 2000).unwrap();
+        assert_eq!(response.transport, DnsTransport::Tcp);
+        assert_eq!(addresses(&response), ["192.0.2.1"]);
+    }
+
+    #[test]
+    fn truncated_udp_answer_is_retried_over_tcp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = socket.local_addr().unwrap();
+        serve_udp(socket, truncated);
+        serve_tcp(TcpListener::bind(addr).unwrap(), answer);
+
+        let response = query(addr, DnsTransport::Udp, 2000).unwrap();
+        assert_eq!(response.transport, DnsTransport::Tcp);
+        assert!(!response.truncated);
+        assert_eq!(addresses(&response), ["192.0.2.1"]);
+    }
+
+    #[test]
+    fn nxdomain_is_a_response_not_an_error() {
+        let response = query(udp_stub(nxdomain), DnsTransport::Udp, 2000).unwrap();
+        assert_eq!(response.response_code, 3);
+        assert_eq!(response.response_code_name, "Non-Existent Domain");
+        assert!(response.answers.is_empty());
+    }
+
+    #[test]
+    fn udp_times_out_when_nobody_answers() {
+        // Bound but never read, so the query goes unanswered
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let started = Instant::now();
+        let err = query(silent.local_addr().unwrap(), DnsTransport::Udp, 200).unwrap_err();
+        assert!(err.starts_with("Timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn tcp_rejects_an_answer_to_another_query() {
+        let err = query(tcp_stub(wrong_id), DnsTransport::Tcp, 2000).unwrap_err();
+        assert!(err.contains("does not match the query id"), "{}", err);
+    }
+}
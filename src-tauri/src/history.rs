@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tauri::State;
+
+use crate::monitor::MonitorState;
+use crate::ProcessInfo;
+
+const DEFAULT_WINDOW: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_RESOLUTION: Duration = Duration::from_secs(2);
+/// sysinfo needs some time between refreshes to compute meaningful CPU usage.
+const MIN_RESOLUTION: Duration = Duration::from_millis(500);
+/// Longer and the monitor would look frozen: watch rules and live updates follow its samples.
+const MAX_RESOLUTION: Duration = Duration::from_secs(5 * 60);
+const MAX_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Samples of every process are held in memory, so window / resolution is capped too.
+const MAX_SAMPLES_PER_SERIES: u128 = 4320;
+
+#[derive(Serialize, Clone, Copy)]
+pub struct HistorySample {
+    /// Unix time in milliseconds.
+    pub ts: i64,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct HistorySeries {
+    /// `None` for the system-wide series.
+    pub pid: Option<u32>,
+    pub name: Option<String>,
+    /// Set once the process is gone; its samples are kept until they age out.
+    pub exited_at: Option<i64>,
+    pub samples: Vec<HistorySample>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct HistoryConfig {
+    pub window_secs: u64,
+    pub resolution_ms: u64,
+}
+
+struct ProcessSeries {
+    name: String,
+    exited_at: Option<i64>,
+    samples: VecDeque<HistorySample>,
+}
+
+/// Ring buffers of CPU/memory samples per PID plus system totals, fed by the monitor thread.
+pub struct ProcessHistory {
+    window: Duration,
+    resolution: Duration,
+    processes: HashMap<u32, ProcessSeries>,
+    system: VecDeque<HistorySample>,
+}
+
+impl Default for ProcessHistory {
+    fn default() -> Self {
+        ProcessHistory {
+            window: DEFAULT_WINDOW,
+            resolution: DEFAULT_RESOLUTION,
+            processes: HashMap::new(),
+            system: VecDeque::new(),
+        }
+    }
+}
+
+impl ProcessHistory {
+    /// How often the monitor should take a sample.
+    pub fn resolution(&self) -> Duration {
+        self.resolution
+    }
+
+    pub fn config(&self) -> HistoryConfig {
+        HistoryConfig {
+            window_secs: self.window.as_secs(),
+            resolution_ms: self.resolution.as_millis() as u64,
+        }
+    }
+
+    pub fn record(&mut self, ts: i64, processes: &[ProcessInfo], system: HistorySample) {
+        let sample_of = |p: &ProcessInfo| HistorySample { ts, cpu_usage: p.cpu_usage, memory: p.memory };
+
+        let mut seen = std::collections::HashSet::with_capacity(processes.len());
+        for p in processes {
+            seen.insert(p.pid);
+            let series = self.processes.entry(p.pid).or_insert_with(|| ProcessSeries {
+                name: p.name.clone(),
+                exited_at: None,
+                samples: VecDeque::new(),
+            });
+            // A PID that was reused by a different process starts a fresh series
+            if series.exited_at.is_some() || series.name != p.name {
+                *series = ProcessSeries {
+                    name: p.name.clone(),
+                    exited_at: None,
+                    samples: VecDeque::new(),
+                };
+            }
+            series.samples.push_back(sample_of(p));
+        }
+
+        for (pid, series) in self.processes.iter_mut() {
+            if series.exited_at.is_none() && !seen.contains(pid) {
+                series.exited_at = Some(ts);
+            }
+        }
+
+        self.system.push_back(system);
+        self.prune(ts);
+    }
+
+    fn prune(&mut self, now: i64) {
+        let cutoff = now - self.window.as_millis() as i64;
+        let expire = |samples: &mut VecDeque<HistorySample>| {
+            while samples.front().is_some_and(|s| s.ts < cutoff) {
+                samples.pop_front();
+            }
+        };
+
+        expire(&mut self.system);
+        for series in self.processes.values_mut() {
+            expire(&mut series.samples);
+        }
+        self.processes.retain(|_, series| !series.samples.is_empty());
+    }
+
+    fn series(&self, pid: Option<u32>, since: i64) -> Option<HistorySeries> {
+        let recent = |samples: &VecDeque<HistorySample>| samples.iter().filter(|s| s.ts >= since).copied().collect();
+        match pid {
+            None => Some(HistorySeries {
+                pid: None,
+                name: None,
+                exited_at: None,
+                samples: recent(&self.system),
+            }),
+            Some(pid) => self.processes.get(&pid).map(|series| HistorySeries {
+                pid: Some(pid),
+                name: Some(series.name.clone()),
+                exited_at: series.exited_at,
+                samples: recent(&series.samples),
+            }),
+        }
+    }
+}
+
+/// Samples for `pid` (or the whole system when omitted) over the last `range` seconds.
+/// Without `range` the full retention window is returned.
+#[tauri::command]
+pub fn get_process_history(
+    pid: Option<u32>,
+    range: Option<u64>,
+    monitor: State<'_, MonitorState>,
+) -> Result<HistorySeries, String> {
+    let history = monitor.0.history.lock().unwrap();
+    let range = range.map(Duration::from_secs).unwrap_or(history.window);
+    let range_ms = i64::try_from(range.as_millis()).map_err(|_| format!("Range of {}s is too large", range.as_secs()))?;
+    let since = chrono::Utc::now().timestamp_millis().saturating_sub(range_ms);
+    history
+        .series(pid, since)
+        .ok_or_else(|| format!("No history for process {}", pid.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub fn get_history_config(monitor: State<'_, MonitorState>) -> HistoryConfig {
+    monitor.0.history.lock().unwrap().config()
+}
+
+/// Changes the retention window and sampling interval. Samples already recorded are
+/// kept (or trimmed to the new window on the next sample).
+#[tauri::command]
+pub fn set_history_config(config: HistoryConfig, monitor: State<'_, MonitorState>) -> Result<(), String> {
+    let window = Duration::from_secs(config.window_secs);
+    // `prune` works in signed milliseconds, so anything past i64 would wrap the cutoff
+    let in_range = i64::try_from(window.as_millis()).is_ok() && !window.is_zero() && window <= MAX_WINDOW;
+    if !in_range {
+        return Err(format!("Window must be between 1 and {} seconds", MAX_WINDOW.as_secs()));
+    }
+    let resolution = Duration::from_millis(config.resolution_ms).max(MIN_RESOLUTION);
+    if resolution > MAX_RESOLUTION {
+        return Err(format!("Resolution must be at most {} ms", MAX_RESOLUTION.as_millis()));
+    }
+    let samples = window.as_millis() / resolution.as_millis();
+    if samples > MAX_SAMPLES_PER_SERIES {
+        return Err(format!(
+            "A {}s window at {}ms keeps {} samples per process, more than {}; use a coarser resolution",
+            window.as_secs(),
+            resolution.as_millis(),
+            samples,
+            MAX_SAMPLES_PER_SERIES
+        ));
+    }
+
+    let mut history = monitor.0.history.lock().unwrap();
+    history.window = window;
+    history.resolution = resolution;
+    // The sampler may be asleep on the old interval
+    monitor.0.config_changed.notify_all();
+    Ok(())
+}
@@ -1,13 +1,25 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use sysinfo::System;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use std::process::Command;
-use std::os::windows::process::CommandExt;
 
+mod dns;
+mod history;
+mod monitor;
+mod network;
+mod overview;
+mod ports;
+mod process_control;
+mod process_details;
+mod process_limits;
+mod process_tree;
+mod query;
+mod run_history;
 mod scheduler;
-
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+mod snapshot;
+mod task_output;
+mod timer_wheel;
+mod watch;
+mod window;
 
 #[derive(Serialize, Deserialize, Clone)]
 struct ProcessInfo {
@@ -19,108 +31,78 @@ struct ProcessInfo {
     memory: u64,
     cpu_usage: f32,
     status: String,
+    user: Option<String>,
     ports: Vec<u16>,
+    sockets: Vec<ports::SocketInfo>,
     window_handle: Option<String>,
 }
 
-fn get_netstat_ports() -> HashMap<u32, Vec<u16>> {
-    let mut map: HashMap<u32, Vec<u16>> = HashMap::new();
-    
-    // Execute netstat -ano
-    if let Ok(output) = Command::new("netstat")
-        .args(["-ano"])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output() 
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
-            // Example line: "  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       4"
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 5 && (parts[0] == "TCP" || parts[0] == "UDP") {
-                // Extract PID (last column)
-                if let Ok(pid) = parts[parts.len() - 1].parse::<u32>() {
-                    // Extract Port from Local Address (2nd column like 0.0.0.0:135)
-                    if let Some(colon_pos) = parts[1].rfind(':') {
-                        if let Ok(port) = parts[1][colon_pos + 1..].parse::<u16>() {
-                            map.entry(pid).or_default().push(port);
-                        }
-                    }
-                }
-            }
+impl ProcessInfo {
+    fn from_process(process: &sysinfo::Process) -> Self {
+        ProcessInfo {
+            pid: process.pid().as_u32(),
+            ppid: process.parent().map(|p| p.as_u32()),
+            name: process.name().to_string_lossy().into_owned(),
+            exe: process.exe().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default(),
+            cmd: process.cmd().iter().map(|s| s.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" "),
+            memory: process.memory(),
+            cpu_usage: process.cpu_usage(),
+            status: process.status().to_string(),
+            user: None,
+            ports: Vec::new(),
+            sockets: Vec::new(),
+            window_handle: None,
         }
     }
-    
-    // Dedup ports
-    for ports in map.values_mut() {
-        ports.sort();
-        ports.dedup();
-    }
-    
-    map
 }
 
-fn get_window_handles() -> HashMap<u32, String> {
-    let mut map = HashMap::new();
-    
-    // Using PowerShell to get MainWindowHandle
-    // Warning: This is relatively slow.
-    let ps_script = "(Get-Process).Where({$_.MainWindowHandle -ne 0}) | Select-Object Id, MainWindowHandle | ConvertTo-Json -Compress";
-    
-    if let Ok(output) = Command::new("powershell")
-        .args(["-NoProfile", "-Command", ps_script])
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()
-    {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        
-        #[derive(Deserialize)]
-        #[allow(non_snake_case)]
-        struct HandleInfo {
-            Id: u32,
-            MainWindowHandle: usize, // Handle can be large
-        }
-
-        // Handle single object or array of objects from PowerShell JSON
-        if let Ok(info_list) = serde_json::from_str::<Vec<HandleInfo>>(&stdout) {
-            for info in info_list {
-                map.insert(info.Id, format!("0x{:X}", info.MainWindowHandle));
-            }
-        } else if let Ok(info) = serde_json::from_str::<HandleInfo>(&stdout) {
-             map.insert(info.Id, format!("0x{:X}", info.MainWindowHandle));
+fn attach_sockets(processes: &mut [ProcessInfo], sockets_map: &HashMap<u32, Vec<ports::SocketInfo>>) {
+    for info in processes.iter_mut() {
+        if let Some(sockets) = sockets_map.get(&info.pid) {
+            info.ports = ports::local_ports(sockets);
+            info.sockets = sockets.clone();
         }
     }
-    
-    map
 }
 
+/// Processes matching `query.filter`, sorted and paginated. Without a query every
+/// process is returned, ordered by PID.
 #[tauri::command]
-fn get_processes() -> Vec<ProcessInfo> {
-    let mut sys = System::new_all();
-    sys.refresh_all();
-    
-    // Fetch additional info
-    let ports_map = get_netstat_ports();
-    let handles_map = get_window_handles();
-
-    let mut processes = Vec::new();
-
-    for (pid, process) in sys.processes() {
-        let pid_val = pid.as_u32();
-        processes.push(ProcessInfo {
-            pid: pid_val,
-            ppid: process.parent().map(|p| p.as_u32()),
-            name: process.name().to_string_lossy().into_owned(),
-            exe: process.exe().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default(),
-            cmd: process.cmd().iter().map(|s| s.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" "),
-            memory: process.memory(),
-            cpu_usage: process.cpu_usage(),
-            status: process.status().to_string(),
-            ports: ports_map.get(&pid_val).cloned().unwrap_or_default(),
-            window_handle: handles_map.get(&pid_val).cloned(),
-        });
+fn get_processes(
+    query: Option<query::ProcessQuery>,
+    monitor: State<'_, monitor::MonitorState>,
+) -> Result<query::ProcessPage, query::QueryError> {
+    let query = query.unwrap_or_default();
+    // Reject bad filters before doing any of the slow collection below
+    let filter = query::Query::parse(query.filter.as_deref().unwrap_or_default())?;
+
+    // Fetch additional info; window enumeration runs alongside netstat and is dropped if it stalls
+    let pending_handles = window::request_main_window_handles();
+    let sockets_map = ports::get_netstat_sockets();
+
+    let mut processes = monitor.0.processes();
+    attach_sockets(&mut processes, &sockets_map);
+
+    processes.retain(|p| filter.matches(p));
+    let total = processes.len();
+
+    let sort_by = query.sort_by.unwrap_or(query::Field::Pid);
+    processes.sort_by(|a, b| {
+        let ordering = query::compare_by(sort_by, a, b).then(a.pid.cmp(&b.pid));
+        if query.descending { ordering.reverse() } else { ordering }
+    });
+    let mut processes: Vec<ProcessInfo> = processes
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    let handles_map = pending_handles.wait();
+    for info in processes.iter_mut() {
+        info.window_handle = handles_map.get(&info.pid).cloned();
     }
 
-    processes
+    Ok(query::ProcessPage { total, processes })
 }
 
 #[tauri::command]
@@ -128,28 +110,11 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
-use std::net::ToSocketAddrs;
-
-#[tauri::command]
-fn resolve_domain(domain: &str) -> Result<Vec<String>, String> {
-    let domain_with_port = format!("{}:80", domain);
-    match domain_with_port.to_socket_addrs() {
-        Ok(addrs) => {
-            let ips: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
-             // Simple dedup
-            let mut ips = ips;
-            ips.sort();
-            ips.dedup();
-            Ok(ips)
-        },
-        Err(e) => Err(e.to_string()),
-    }
-}
-
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     Manager,
+    State,
     image::Image,
 };
 use tauri_plugin_autostart::MacosLauncher;
@@ -162,7 +127,18 @@ pub fn run() {
         .setup(|app| {
             // Initialize Scheduler
             let scheduler_state = scheduler::init(app.handle().clone());
-            app.manage(scheduler_state);
+            app.manage(scheduler_state.clone());
+
+            // Start sampling processes in the background
+            let monitor_state = monitor::init(app.handle().clone());
+            let overview_state = overview::init(app.handle().clone(), monitor_state.clone());
+            let watch_state = watch::init(app.handle().clone(), monitor_state.clone(), scheduler_state);
+            app.manage(monitor_state);
+            app.manage(overview_state);
+            app.manage(watch_state);
+
+            let network_state = network::init(app.handle().clone());
+            app.manage(network_state);
 
             let quit_i = MenuItem::with_id(app, "quit", "Quit aTools", true, None::<&str>)?;
             let show_i = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
@@ -195,11 +171,12 @@ pub fn run() {
                     }
                     _ => {}
                 })
-                .on_tray_icon_event(|tray, event| match event {
-                    TrayIconEvent::Click {
+                .on_tray_icon_event(|tray, event| {
+                    if let TrayIconEvent::Click {
                         button: MouseButton::Left,
                         ..
-                    } => {
+                    } = event
+                    {
                         let app = tray.app_handle();
                         if let Some(window) = app.get_webview_window("main") {
                              let _ = window.unminimize();
@@ -207,14 +184,13 @@ pub fn run() {
                             let _ = window.set_focus();
                         }
                     }
-                    _ => {}
                 })
                 .build(app)?;
 
             Ok(())
         })
-        .on_window_event(|window, event| match event {
-            tauri::WindowEvent::CloseRequested { api, .. } => {
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
                 let win = window.clone();
                 // Prevent the app from closing, just hide it
                 // We typically only want to intercept the Main Window
@@ -223,18 +199,49 @@ pub fn run() {
                     api.prevent_close();
                 }
             }
-            _ => {}
         })
         .invoke_handler(tauri::generate_handler![
             greet, 
             get_processes, 
-            resolve_domain,
+            dns::resolve_domain,
+            dns::dns_query,
+            dns::reverse_lookup,
+            dns::dns_bulk_lookup,
+            ports::get_connections,
+            ports::find_port_owner,
+            ports::kill_port_owner,
+            process_control::kill_process,
+            process_control::kill_process_tree,
+            process_control::suspend_process,
+            process_control::resume_process,
+            process_tree::get_process_tree,
+            snapshot::export_processes,
+            snapshot::diff_process_snapshots,
+            process_details::get_process_details,
+            process_limits::set_process_priority,
+            process_limits::set_process_affinity,
+            history::get_process_history,
+            history::get_history_config,
+            history::set_history_config,
+            overview::get_system_overview,
+            network::get_network_interfaces,
+            window::list_windows,
+            window::focus_window,
+            window::minimize_window,
+            window::close_window,
+            window::set_window_topmost,
+            watch::get_watch_rules,
+            watch::add_watch_rule,
+            watch::update_watch_rule,
+            watch::delete_watch_rule,
             scheduler::get_tasks,
             scheduler::add_task,
             scheduler::update_task,
             scheduler::delete_task,
             scheduler::run_task_manual,
-            scheduler::stop_task
+            scheduler::stop_task,
+            run_history::get_task_runs,
+            run_history::get_run_output
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,248 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Instant;
+use sysinfo::{Gid, Groups, ProcessRefreshKind, ProcessesToUpdate, System, Uid, UpdateKind, Users};
+use tauri::{AppHandle, Emitter};
+
+use crate::history::{HistorySample, ProcessHistory};
+use crate::ProcessInfo;
+
+/// The fields that move between samples; sent for processes that were already known.
+#[derive(Serialize, Clone, PartialEq)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub memory: u64,
+    pub cpu_usage: f32,
+    pub status: String,
+}
+
+/// Payload of the `process-snapshot` event. `added` entries carry no ports or
+/// window handle; those are only collected by `get_processes`.
+#[derive(Serialize, Clone)]
+pub struct ProcessSnapshotDiff {
+    pub added: Vec<ProcessInfo>,
+    pub removed: Vec<u32>,
+    pub changed: Vec<ProcessUsage>,
+}
+
+/// The process list of one refresh, handed to `subscribe`rs.
+#[derive(Clone)]
+pub struct ProcessSample {
+    /// Milliseconds since the Unix epoch, the same as the history sample's.
+    pub ts: i64,
+    pub processes: Arc<Vec<ProcessInfo>>,
+}
+
+pub struct ProcessMonitor {
+    /// Long-lived so sysinfo can compute CPU usage from the delta between refreshes.
+    pub system: Mutex<System>,
+    /// Also decides how often the background thread samples.
+    pub history: Mutex<ProcessHistory>,
+    /// Notified with `history` held when the sampling interval changes.
+    pub config_changed: Condvar,
+    /// Resolves process owners to names; reloaded when an unknown user shows up.
+    pub users: Mutex<Users>,
+    /// Same for process groups; only used by the details view.
+    pub groups: Mutex<Groups>,
+    subscribers: Mutex<Vec<mpsc::Sender<ProcessSample>>>,
+    pub app_handle: AppHandle,
+}
+
+#[derive(Clone)]
+pub struct MonitorState(pub Arc<ProcessMonitor>);
+
+impl ProcessMonitor {
+    pub fn new(app_handle: AppHandle) -> Self {
+        let mut system = System::new();
+        refresh(&mut system);
+        ProcessMonitor {
+            system: Mutex::new(system),
+            history: Mutex::new(ProcessHistory::default()),
+            config_changed: Condvar::new(),
+            users: Mutex::new(Users::new_with_refreshed_list()),
+            groups: Mutex::new(Groups::new_with_refreshed_list()),
+            subscribers: Mutex::new(Vec::new()),
+            app_handle,
+        }
+    }
+
+    /// Current process list built from the last sample, without ports or window handles.
+    pub fn processes(&self) -> Vec<ProcessInfo> {
+        let sys = self.system.lock().unwrap();
+        let mut users = self.users.lock().unwrap();
+        let live = || sys.processes().values().filter(|p| p.thread_kind().is_none());
+
+        if live().any(|p| p.user_id().is_some_and(|uid| users.get_user_by_id(uid).is_none())) {
+            users.refresh();
+        }
+
+        live()
+            .map(|p| ProcessInfo {
+                user: p
+                    .user_id()
+                    .and_then(|uid| users.get_user_by_id(uid))
+                    .map(|u| u.name().to_string()),
+                ..ProcessInfo::from_process(p)
+            })
+            .collect()
+    }
+
+    /// Blocks until one resolution has passed since `last_sample`, going by the current
+    /// resolution even if it is changed while we wait.
+    fn wait_for_next_sample(&self, last_sample: Instant) {
+        let mut history = self.history.lock().unwrap();
+        loop {
+            let due = last_sample + history.resolution();
+            let now = Instant::now();
+            if now >= due {
+                return;
+            }
+            history = self.config_changed.wait_timeout(history, due - now).unwrap().0;
+        }
+    }
+
+    /// Receives every sample taken from now on, exactly once and in order.
+    pub fn subscribe(&self) -> mpsc::Receiver<ProcessSample> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn publish(&self, sample: ProcessSample) {
+        // Receivers that were dropped stop getting samples
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(sample.clone()).is_ok());
+    }
+
+    /// Name of the user `uid`, reloading the user list once if it is unknown.
+    pub fn user_name(&self, uid: &Uid) -> Option<String> {
+        let mut users = self.users.lock().unwrap();
+        if users.get_user_by_id(uid).is_none() {
+            users.refresh();
+        }
+        users.get_user_by_id(uid).map(|u| u.name().to_string())
+    }
+
+    /// Name of the group `gid`, reloading the group list once if it is unknown.
+    pub fn group_name(&self, gid: &Gid) -> Option<String> {
+        let mut groups = self.groups.lock().unwrap();
+        let find = |groups: &Groups| groups.list().iter().find(|g| g.id() == gid).map(|g| g.name().to_string());
+        if find(&groups).is_none() {
+            groups.refresh();
+        }
+        find(&groups)
+    }
+
+    /// System-wide CPU and used memory from the last sample.
+    fn system_sample(&self, ts: i64) -> HistorySample {
+        let sys = self.system.lock().unwrap();
+        HistorySample {
+            ts,
+            cpu_usage: sys.global_cpu_usage(),
+            memory: sys.used_memory(),
+        }
+    }
+}
+
+fn refresh(system: &mut System) {
+    system.refresh_cpu_usage();
+    system.refresh_memory();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet)
+            .with_user(UpdateKind::OnlyIfNotSet),
+    );
+}
+
+fn usage_of(info: &ProcessInfo) -> ProcessUsage {
+    ProcessUsage {
+        pid: info.pid,
+        memory: info.memory,
+        cpu_usage: info.cpu_usage,
+        status: info.status.clone(),
+    }
+}
+
+fn diff(previous: &HashMap<u32, ProcessUsage>, current: &[ProcessInfo]) -> ProcessSnapshotDiff {
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+
+    for info in current {
+        match previous.get(&info.pid) {
+            None => added.push(info.clone()),
+            Some(old) => {
+                let usage = usage_of(info);
+                // Ignore sub-0.1% CPU jitter so idle processes don't flood the event
+                if usage.memory != old.memory
+                    || usage.status != old.status
+                    || (usage.cpu_usage - old.cpu_usage).abs() >= 0.1
+                {
+                    changed.push(usage);
+                }
+            }
+        }
+    }
+
+    let current_pids: std::collections::HashSet<u32> = current.iter().map(|p| p.pid).collect();
+    let removed = previous
+        .keys()
+        .filter(|pid| !current_pids.contains(pid))
+        .copied()
+        .collect();
+
+    ProcessSnapshotDiff { added, removed, changed }
+}
+
+pub fn init(app_handle: AppHandle) -> MonitorState {
+    let monitor = ProcessMonitor::new(app_handle);
+    let state = MonitorState(Arc::new(monitor));
+
+    // Start background thread
+    let thread_state = state.clone();
+    thread::spawn(move || {
+        let monitor = &thread_state.0;
+        let mut previous: HashMap<u32, ProcessUsage> = monitor
+            .processes()
+            .iter()
+            .map(|p| (p.pid, usage_of(p)))
+            .collect();
+
+        let mut last_sample = Instant::now();
+        loop {
+            monitor.wait_for_next_sample(last_sample);
+            last_sample = Instant::now();
+
+            refresh(&mut monitor.system.lock().unwrap());
+            let current = Arc::new(monitor.processes());
+
+            let ts = chrono::Utc::now().timestamp_millis();
+            let system_sample = monitor.system_sample(ts);
+            monitor.history.lock().unwrap().record(ts, &current, system_sample);
+            monitor.publish(ProcessSample { ts, processes: current.clone() });
+
+            let diff = diff(&previous, &current);
+            // Only keep the fields we compare against next time
+            for pid in &diff.removed {
+                previous.remove(pid);
+            }
+            for info in &diff.added {
+                previous.insert(info.pid, usage_of(info));
+            }
+            for usage in &diff.changed {
+                previous.insert(usage.pid, usage.clone());
+            }
+
+            if !diff.added.is_empty() || !diff.removed.is_empty() || !diff.changed.is_empty() {
+                let _ = monitor.app_handle.emit("process-snapshot", diff);
+            }
+        }
+    });
+
+    state
+}
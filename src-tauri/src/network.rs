@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::Networks;
+use tauri::{AppHandle, Emitter, State};
+
+/// How often interface counters are sampled and `network-throughput` is emitted.
+const THROUGHPUT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Clone, Default)]
+pub struct InterfaceThroughput {
+    pub name: String,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct InterfaceAddress {
+    pub addr: String,
+    pub prefix: u8,
+}
+
+#[derive(Serialize, Clone)]
+pub struct NetworkInterfaceInfo {
+    pub name: String,
+    pub mac_address: String,
+    pub ipv4: Vec<InterfaceAddress>,
+    pub ipv6: Vec<InterfaceAddress>,
+    pub mtu: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub total_packets_received: u64,
+    pub total_packets_transmitted: u64,
+    pub total_errors_on_received: u64,
+    pub total_errors_on_transmitted: u64,
+    /// Rates from the most recent background sample.
+    pub throughput: InterfaceThroughput,
+}
+
+pub struct NetworkSampler {
+    /// Only refreshed by the sampler thread; refreshing elsewhere would steal its deltas.
+    pub networks: Mutex<Networks>,
+    pub throughput: Mutex<HashMap<String, InterfaceThroughput>>,
+}
+
+#[derive(Clone)]
+pub struct NetworkState(pub Arc<NetworkSampler>);
+
+impl NetworkSampler {
+    pub fn new() -> Self {
+        NetworkSampler {
+            networks: Mutex::new(Networks::new_with_refreshed_list()),
+            throughput: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn sample(&self, elapsed: Duration) -> Vec<InterfaceThroughput> {
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let mut networks = self.networks.lock().unwrap();
+        networks.refresh(true);
+
+        let mut rates: Vec<InterfaceThroughput> = networks
+            .list()
+            .iter()
+            .map(|(name, data)| InterfaceThroughput {
+                name: name.clone(),
+                rx_bytes_per_sec: data.received() as f64 / secs,
+                tx_bytes_per_sec: data.transmitted() as f64 / secs,
+                rx_packets_per_sec: data.packets_received() as f64 / secs,
+                tx_packets_per_sec: data.packets_transmitted() as f64 / secs,
+            })
+            .collect();
+        rates.sort_by(|a, b| a.name.cmp(&b.name));
+
+        *self.throughput.lock().unwrap() = rates.iter().map(|r| (r.name.clone(), r.clone())).collect();
+        rates
+    }
+}
+
+pub fn init(app_handle: AppHandle) -> NetworkState {
+    let state = NetworkState(Arc::new(NetworkSampler::new()));
+
+    // Start background thread
+    let thread_state = state.clone();
+    thread::spawn(move || {
+        let mut last = Instant::now();
+        loop {
+            thread::sleep(THROUGHPUT_INTERVAL);
+            let now = Instant::now();
+            let rates = thread_state.0.sample(now - last);
+            last = now;
+            let _ = app_handle.emit("network-throughput", rates);
+        }
+    });
+
+    state
+}
+
+#[tauri::command]
+pub fn get_network_interfaces(state: State<'_, NetworkState>) -> Vec<NetworkInterfaceInfo> {
+    let networks = state.0.networks.lock().unwrap();
+    let throughput = state.0.throughput.lock().unwrap();
+
+    let mut interfaces: Vec<NetworkInterfaceInfo> = networks
+        .list()
+        .iter()
+        .map(|(name, data)| {
+            let (ipv4, ipv6): (Vec<_>, Vec<_>) = data.ip_networks().iter().partition(|n| n.addr.is_ipv4());
+            let to_addresses = |nets: Vec<&sysinfo::IpNetwork>| {
+                nets.into_iter()
+                    .map(|n| InterfaceAddress { addr: n.addr.to_string(), prefix: n.prefix })
+                    .collect()
+            };
+            NetworkInterfaceInfo {
+                name: name.clone(),
+                mac_address: data.mac_address().to_string(),
+                ipv4: to_addresses(ipv4),
+                ipv6: to_addresses(ipv6),
+                mtu: data.mtu(),
+                total_received: data.total_received(),
+                total_transmitted: data.total_transmitted(),
+                total_packets_received: data.total_packets_received(),
+                total_packets_transmitted: data.total_packets_transmitted(),
+                total_errors_on_received: data.total_errors_on_received(),
+                total_errors_on_transmitted: data.total_errors_on_transmitted(),
+                throughput: throughput.get(name).cloned().unwrap_or_else(|| InterfaceThroughput {
+                    name: name.clone(),
+                    ..Default::default()
+                }),
+            }
+        })
+        .collect();
+    interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+    interfaces
+}
@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use sysinfo::{Components, Disks, System};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::monitor::MonitorState;
+
+/// How often the `system-overview` event is pushed to the dashboard.
+const OVERVIEW_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Clone)]
+pub struct CoreUsage {
+    pub name: String,
+    pub cpu_usage: f32,
+    /// MHz
+    pub frequency: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct DiskInfo {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub kind: String,
+    pub removable: bool,
+    pub total_space: u64,
+    pub available_space: u64,
+}
+
+#[derive(Serialize, Clone)]
+pub struct TemperatureInfo {
+    pub label: String,
+    /// Degrees Celsius.
+    pub temperature: Option<f32>,
+    pub max: Option<f32>,
+    pub critical: Option<f32>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SystemOverview {
+    pub cpu_brand: String,
+    pub cpu_usage: f32,
+    pub cores: Vec<CoreUsage>,
+    /// Always zero on Windows, which has no load average.
+    pub load_average: LoadAverage,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    /// Seconds.
+    pub uptime: u64,
+    /// Seconds since the Unix epoch.
+    pub boot_time: u64,
+    pub os_name: Option<String>,
+    pub os_version: Option<String>,
+    pub kernel_version: Option<String>,
+    pub host_name: Option<String>,
+    pub disks: Vec<DiskInfo>,
+    pub temperatures: Vec<TemperatureInfo>,
+}
+
+/// Disks and sensors are kept around between refreshes; CPU and memory come from the
+/// process monitor, which already refreshes them on every sample.
+pub struct OverviewSampler {
+    pub disks: Mutex<Disks>,
+    pub components: Mutex<Components>,
+    pub monitor: MonitorState,
+}
+
+#[derive(Clone)]
+pub struct OverviewState(pub Arc<OverviewSampler>);
+
+impl OverviewSampler {
+    pub fn new(monitor: MonitorState) -> Self {
+        OverviewSampler {
+            disks: Mutex::new(Disks::new_with_refreshed_list()),
+            components: Mutex::new(Components::new_with_refreshed_list()),
+            monitor,
+        }
+    }
+
+    pub fn sample(&self) -> SystemOverview {
+        let disks = {
+            let mut disks = self.disks.lock().unwrap();
+            // Pick up mounts that appeared or vanished since last time
+            disks.refresh(true);
+            disks
+                .list()
+                .iter()
+                .map(|d| DiskInfo {
+                    name: d.name().to_string_lossy().into_owned(),
+                    mount_point: d.mount_point().to_string_lossy().into_owned(),
+                    file_system: d.file_system().to_string_lossy().into_owned(),
+                    kind: d.kind().to_string(),
+                    removable: d.is_removable(),
+                    total_space: d.total_space(),
+                    available_space: d.available_space(),
+                })
+                .collect()
+        };
+
+        let temperatures = {
+            let mut components = self.components.lock().unwrap();
+            components.refresh(true);
+            components
+                .list()
+                .iter()
+                .map(|c| TemperatureInfo {
+                    label: c.label().to_string(),
+                    temperature: c.temperature(),
+                    max: c.max(),
+                    critical: c.critical(),
+                })
+                .collect()
+        };
+
+        let mut sys = self.monitor.0.system.lock().unwrap();
+        // Usage is kept fresh by the monitor thread, frequency is not
+        sys.refresh_cpu_frequency();
+        let load = System::load_average();
+
+        SystemOverview {
+            cpu_brand: sys.cpus().first().map(|c| c.brand().to_string()).unwrap_or_default(),
+            cpu_usage: sys.global_cpu_usage(),
+            cores: sys
+                .cpus()
+                .iter()
+                .map(|c| CoreUsage {
+                    name: c.name().to_string(),
+                    cpu_usage: c.cpu_usage(),
+                    frequency: c.frequency(),
+                })
+                .collect(),
+            load_average: LoadAverage { one: load.one, five: load.five, fifteen: load.fifteen },
+            total_memory: sys.total_memory(),
+            used_memory: sys.used_memory(),
+            total_swap: sys.total_swap(),
+            used_swap: sys.used_swap(),
+            uptime: System::uptime(),
+            boot_time: System::boot_time(),
+            os_name: System::name(),
+            os_version: System::long_os_version(),
+            kernel_version: System::kernel_version(),
+            host_name: System::host_name(),
+            disks,
+            temperatures,
+        }
+    }
+}
+
+pub fn init(app_handle: AppHandle, monitor: MonitorState) -> OverviewState {
+    let state = OverviewState(Arc::new(OverviewSampler::new(monitor)));
+
+    // Start background thread
+    let thread_state = state.clone();
+    thread::spawn(move || loop {
+        thread::sleep(OVERVIEW_INTERVAL);
+        let overview = thread_state.0.sample();
+        let _ = app_handle.emit("system-overview", overview);
+    });
+
+    state
+}
+
+#[tauri::command]
+pub fn get_system_overview(state: State<'_, OverviewState>) -> SystemOverview {
+    state.0.sample()
+}
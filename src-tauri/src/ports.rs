@@ -0,0 +1,596 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::process_control::{self, ProcessError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketProto {
+    Tcp,
+    Udp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TcpState {
+    Listen,
+    Established,
+    SynSent,
+    SynReceived,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Closed,
+    CloseWait,
+    LastAck,
+    Closing,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SocketInfo {
+    pub proto: SocketProto,
+    pub local_addr: String,
+    /// `None` for listening TCP sockets and unconnected UDP sockets.
+    pub remote_addr: Option<String>,
+    /// Only TCP sockets carry a state.
+    pub state: Option<TcpState>,
+}
+
+impl SocketInfo {
+    pub fn local_port(&self) -> Option<u16> {
+        self.local_addr.rsplit_once(':').and_then(|(_, p)| p.parse().ok())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub pid: u32,
+    #[serde(flatten)]
+    pub socket: SocketInfo,
+}
+
+/// Builds the PID -> socket table map used by `get_processes` and `get_connections`.
+///
+/// Windows shells out to `netstat -ano`; Linux reads the kernel socket tables
+/// from `/proc/net/*` and matches socket inodes against `/proc/<pid>/fd`.
+pub fn get_netstat_sockets() -> HashMap<u32, Vec<SocketInfo>> {
+    platform::collect_sockets(&|_| true)
+}
+
+/// Like `get_netstat_sockets`, but only keeps sockets accepted by `filter`. On Linux this
+/// also skips the per-process fd scan entirely when no socket matches.
+pub fn get_netstat_sockets_matching(filter: &dyn Fn(&SocketInfo) -> bool) -> HashMap<u32, Vec<SocketInfo>> {
+    platform::collect_sockets(filter)
+}
+
+/// Sorted, deduplicated local ports of a socket list.
+pub fn local_ports(sockets: &[SocketInfo]) -> Vec<u16> {
+    let mut ports: Vec<u16> = sockets.iter().filter_map(|s| s.local_port()).collect();
+    ports.sort();
+    ports.dedup();
+    ports
+}
+
+#[tauri::command]
+pub fn get_connections() -> Vec<ConnectionInfo> {
+    let mut connections: Vec<ConnectionInfo> = get_netstat_sockets()
+        .into_iter()
+        .flat_map(|(pid, sockets)| sockets.into_iter().map(move |socket| ConnectionInfo { pid, socket }))
+        .collect();
+    connections.sort_by_key(|c| (c.pid, c.socket.local_port()));
+    connections
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortOwner {
+    pub pid: u32,
+    /// Empty if the process exited between the socket scan and the lookup.
+    pub name: String,
+    pub exe: String,
+    pub cmd: String,
+    /// The owner's sockets bound to the requested port.
+    pub sockets: Vec<SocketInfo>,
+}
+
+/// Processes with a socket bound to `port` locally, listeners first.
+/// `proto` narrows the search to TCP or UDP.
+#[tauri::command]
+pub fn find_port_owner(port: u16, proto: Option<SocketProto>) -> Vec<PortOwner> {
+    let sockets_map = get_netstat_sockets_matching(&|s| {
+        s.local_port() == Some(port) && proto.is_none_or(|p| s.proto == p)
+    });
+    if sockets_map.is_empty() {
+        return Vec::new();
+    }
+
+    let pids: Vec<Pid> = sockets_map.keys().map(|pid| Pid::from_u32(*pid)).collect();
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+
+    let mut owners: Vec<PortOwner> = sockets_map
+        .into_iter()
+        .map(|(pid, sockets)| {
+            let process = sys.process(Pid::from_u32(pid));
+            PortOwner {
+                pid,
+                name: process.map(|p| p.name().to_string_lossy().into_owned()).unwrap_or_default(),
+                exe: process
+                    .and_then(|p| p.exe())
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                cmd: process
+                    .map(|p| p.cmd().iter().map(|s| s.to_string_lossy().into_owned()).collect::<Vec<_>>().join(" "))
+                    .unwrap_or_default(),
+                sockets,
+            }
+        })
+        .collect();
+    let listening = |o: &PortOwner| {
+        o.sockets.iter().any(|s| s.state == Some(TcpState::Listen) || s.proto == SocketProto::Udp)
+    };
+    owners.sort_by_key(|o| (!listening(o), o.pid));
+    owners
+}
+
+/// What `kill_port_owner` did to one owner of the port.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum PortKillResult {
+    Killed { pid: u32 },
+    /// Exited before the signal arrived, which is what we wanted.
+    AlreadyGone { pid: u32 },
+    /// Never signalled, see `skip_reason`.
+    Skipped { pid: u32, reason: String },
+    Failed { pid: u32, error: ProcessError },
+}
+
+/// Why `pid` must not be killed even though netstat lists it as an owner.
+fn skip_reason(pid: u32) -> Option<&'static str> {
+    match pid {
+        // Windows reports TIME_WAIT and other orphaned connections under PID 0
+        0 => Some("not owned by any process"),
+        #[cfg(target_os = "windows")]
+        4 => Some("owned by the Windows kernel (System)"),
+        _ if pid == std::process::id() => Some("owned by aTools itself"),
+        _ => None,
+    }
+}
+
+/// Signals every PID with `kill`, carrying on past failures.
+fn kill_owners(pids: impl IntoIterator<Item = u32>, kill: impl Fn(u32) -> Result<(), ProcessError>) -> Vec<PortKillResult> {
+    pids.into_iter()
+        .map(|pid| {
+            if let Some(reason) = skip_reason(pid) {
+                return PortKillResult::Skipped { pid, reason: reason.to_string() };
+            }
+            match kill(pid) {
+                Ok(()) => PortKillResult::Killed { pid },
+                Err(ProcessError::NoSuchProcess { .. }) => PortKillResult::AlreadyGone { pid },
+                Err(error) => PortKillResult::Failed { pid, error },
+            }
+        })
+        .collect()
+}
+
+/// Follow-up to `find_port_owner`: sends `signal` (default SIGTERM) to every process
+/// holding the port and reports what happened to each.
+#[tauri::command]
+pub fn kill_port_owner(
+    port: u16,
+    proto: Option<SocketProto>,
+    signal: Option<String>,
+) -> Vec<PortKillResult> {
+    let owners = find_port_owner(port, proto);
+    kill_owners(owners.into_iter().map(|o| o.pid), |pid| process_control::kill_process(pid, signal.clone()))
+}
+
+/// Parsing of `netstat -ano` output, kept platform-independent so it can be tested anywhere.
+#[cfg(any(target_os = "windows", test))]
+mod netstat {
+    use super::{SocketInfo, SocketProto, TcpState};
+
+    /// (pid, socket) for one line of `netstat -ano`, or `None` for headers and blank lines.
+    pub fn parse_line(line: &str) -> Option<(u32, SocketInfo)> {
+        // Example lines:
+        // "  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       4"
+        // "  UDP    0.0.0.0:123            *:*                                    1234"
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let socket = match (parts.first().copied(), parts.len()) {
+            (Some("TCP"), 5) => SocketInfo {
+                proto: SocketProto::Tcp,
+                local_addr: parts[1].to_string(),
+                remote_addr: remote_addr(parts[2]),
+                state: Some(parse_state(parts[3])),
+            },
+            (Some("UDP"), 4) => SocketInfo {
+                proto: SocketProto::Udp,
+                local_addr: parts[1].to_string(),
+                remote_addr: remote_addr(parts[2]),
+                state: None,
+            },
+            _ => return None,
+        };
+        // PID is the last column
+        let pid = parts[parts.len() - 1].parse::<u32>().ok()?;
+        Some((pid, socket))
+    }
+
+    fn remote_addr(addr: &str) -> Option<String> {
+        // Unconnected sockets show "0.0.0.0:0", "[::]:0" or "*:*"
+        if addr == "*:*" || addr.ends_with(":0") {
+            None
+        } else {
+            Some(addr.to_string())
+        }
+    }
+
+    fn parse_state(state: &str) -> TcpState {
+        match state {
+            "LISTENING" => TcpState::Listen,
+            "ESTABLISHED" => TcpState::Established,
+            "SYN_SENT" => TcpState::SynSent,
+            "SYN_RECEIVED" => TcpState::SynReceived,
+            "FIN_WAIT_1" => TcpState::FinWait1,
+            "FIN_WAIT_2" => TcpState::FinWait2,
+            "TIME_WAIT" => TcpState::TimeWait,
+            "CLOSED" => TcpState::Closed,
+            "CLOSE_WAIT" => TcpState::CloseWait,
+            "LAST_ACK" => TcpState::LastAck,
+            "CLOSING" => TcpState::Closing,
+            _ => TcpState::Unknown,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{netstat, SocketInfo};
+    use std::collections::HashMap;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    pub fn collect_sockets(filter: &dyn Fn(&SocketInfo) -> bool) -> HashMap<u32, Vec<SocketInfo>> {
+        let mut map: HashMap<u32, Vec<SocketInfo>> = HashMap::new();
+
+        // Execute netstat -ano
+        if let Ok(output) = Command::new("netstat")
+            .args(["-ano"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+        {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for (pid, socket) in stdout.lines().filter_map(netstat::parse_line) {
+                if filter(&socket) {
+                    map.entry(pid).or_default().push(socket);
+                }
+            }
+        }
+
+        map
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{SocketInfo, SocketProto, TcpState};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+    const PROC_NET_TABLES: [(&str, SocketProto); 4] = [
+        ("tcp", SocketProto::Tcp),
+        ("tcp6", SocketProto::Tcp),
+        ("udp", SocketProto::Udp),
+        ("udp6", SocketProto::Udp),
+    ];
+
+    pub fn collect_sockets(filter: &dyn Fn(&SocketInfo) -> bool) -> HashMap<u32, Vec<SocketInfo>> {
+        // inode -> socket, from every socket table we know about
+        let mut inode_sockets: HashMap<u64, SocketInfo> = HashMap::new();
+        for (table, proto) in PROC_NET_TABLES {
+            if let Ok(content) = fs::read_to_string(format!("/proc/net/{}", table)) {
+                for (inode, socket) in parse_net_table(&content, proto) {
+                    if filter(&socket) {
+                        inode_sockets.insert(inode, socket);
+                    }
+                }
+            }
+        }
+
+        if inode_sockets.is_empty() {
+            return HashMap::new();
+        }
+        group_by_pid(&inode_sockets, socket_inodes())
+    }
+
+    /// The sockets of `inode_sockets` keyed by every PID holding an fd to them.
+    pub(super) fn group_by_pid(
+        inode_sockets: &HashMap<u64, SocketInfo>,
+        fds: impl IntoIterator<Item = (u32, u64)>,
+    ) -> HashMap<u32, Vec<SocketInfo>> {
+        let mut map: HashMap<u32, Vec<SocketInfo>> = HashMap::new();
+        for (pid, inode) in fds {
+            if let Some(socket) = inode_sockets.get(&inode) {
+                map.entry(pid).or_default().push(socket.clone());
+            }
+        }
+        map
+    }
+
+    /// Parses a `/proc/net/{tcp,tcp6,udp,udp6}` table into (inode, socket) pairs.
+    pub(super) fn parse_net_table(content: &str, proto: SocketProto) -> Vec<(u64, SocketInfo)> {
+        let mut entries = Vec::new();
+        // Example line:
+        // "   0: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 ..."
+        for line in content.lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 10 {
+                continue;
+            }
+            let local = parse_addr(parts[1]);
+            let remote = parse_addr(parts[2]);
+            let inode = parts[9].parse::<u64>().ok();
+            // Inode 0 means the socket is no longer owned by anyone (e.g. TIME_WAIT)
+            if let (Some(local), Some(remote), Some(inode)) = (local, remote, inode) {
+                if inode == 0 {
+                    continue;
+                }
+                let state = match proto {
+                    SocketProto::Tcp => Some(parse_state(parts[3])),
+                    SocketProto::Udp => None,
+                };
+                entries.push((
+                    inode,
+                    SocketInfo {
+                        proto,
+                        local_addr: local.to_string(),
+                        remote_addr: (remote.port() != 0).then(|| remote.to_string()),
+                        state,
+                    },
+                ));
+            }
+        }
+        entries
+    }
+
+    /// Decodes "0100007F:1F90" (IPv4) or the 32-digit IPv6 form. The kernel prints
+    /// each 32-bit word of the address in host byte order, the port in network order.
+    pub(super) fn parse_addr(s: &str) -> Option<SocketAddr> {
+        let (ip_hex, port_hex) = s.split_once(':')?;
+        let port = u16::from_str_radix(port_hex, 16).ok()?;
+        let ip = match ip_hex.len() {
+            8 => IpAddr::V4(Ipv4Addr::from(u32::from_str_radix(ip_hex, 16).ok()?.to_ne_bytes())),
+            32 => {
+                let mut octets = [0u8; 16];
+                for (i, chunk) in octets.chunks_mut(4).enumerate() {
+                    let word = u32::from_str_radix(&ip_hex[i * 8..i * 8 + 8], 16).ok()?;
+                    chunk.copy_from_slice(&word.to_ne_bytes());
+                }
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return None,
+        };
+        Some(SocketAddr::new(ip, port))
+    }
+
+    fn parse_state(code: &str) -> TcpState {
+        match code {
+            "01" => TcpState::Established,
+            "02" => TcpState::SynSent,
+            "03" => TcpState::SynReceived,
+            "04" => TcpState::FinWait1,
+            "05" => TcpState::FinWait2,
+            "06" => TcpState::TimeWait,
+            "07" => TcpState::Closed,
+            "08" => TcpState::CloseWait,
+            "09" => TcpState::LastAck,
+            "0A" => TcpState::Listen,
+            "0B" => TcpState::Closing,
+            _ => TcpState::Unknown,
+        }
+    }
+
+    /// The inode of an fd link target if it is a socket: they read "socket:[12345]".
+    pub(super) fn socket_inode(target: &str) -> Option<u64> {
+        target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+    }
+
+    /// Lists (pid, socket inode) for every socket fd we are allowed to inspect.
+    /// Processes owned by other users are silently skipped unless we run as root.
+    fn socket_inodes() -> Vec<(u32, u64)> {
+        let mut result = Vec::new();
+        let Ok(proc_dir) = fs::read_dir("/proc") else {
+            return result;
+        };
+
+        for entry in proc_dir.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+                continue;
+            };
+            for fd in fds.flatten() {
+                if let Some(inode) = fs::read_link(fd.path()).ok().and_then(|t| socket_inode(&t.to_string_lossy())) {
+                    result.push((pid, inode));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::SocketInfo;
+    use std::collections::HashMap;
+
+    pub fn collect_sockets(_filter: &dyn Fn(&SocketInfo) -> bool) -> HashMap<u32, Vec<SocketInfo>> {
+        HashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket(proto: SocketProto, local: &str, remote: Option<&str>, state: Option<TcpState>) -> SocketInfo {
+        SocketInfo {
+            proto,
+            local_addr: local.to_string(),
+            remote_addr: remote.map(str::to_string),
+            state,
+        }
+    }
+
+    #[test]
+    fn netstat_lines() {
+        let cases: [(&str, Option<(u32, SocketInfo)>); 7] = [
+            (
+                "  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1044",
+                Some((1044, socket(SocketProto::Tcp, "0.0.0.0:135", None, Some(TcpState::Listen)))),
+            ),
+            (
+                "  TCP    192.168.1.20:52814     140.82.112.26:443      ESTABLISHED     7420",
+                Some((
+                    7420,
+                    socket(SocketProto::Tcp, "192.168.1.20:52814", Some("140.82.112.26:443"), Some(TcpState::Established)),
+                )),
+            ),
+            (
+                "  TCP    [::1]:49670            [::1]:5432             TIME_WAIT       0",
+                Some((0, socket(SocketProto::Tcp, "[::1]:49670", Some("[::1]:5432"), Some(TcpState::TimeWait)))),
+            ),
+            (
+                "  TCP    [::]:445               [::]:0                 LISTENING       4",
+                Some((4, socket(SocketProto::Tcp, "[::]:445", None, Some(TcpState::Listen)))),
+            ),
+            (
+                "  UDP    0.0.0.0:5353           *:*                                    2212",
+                Some((2212, socket(SocketProto::Udp, "0.0.0.0:5353", None, None))),
+            ),
+            ("  Proto  Local Address          Foreign Address        State           PID", None),
+            ("Active Connections", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(netstat::parse_line(line), expected, "{}", line);
+        }
+    }
+
+    /// The kernel prints address words in host byte order; these lines come from x86.
+    #[cfg(all(target_os = "linux", target_endian = "little"))]
+    #[test]
+    fn proc_net_addresses() {
+        let cases = [
+            ("0100007F:1F90", Some("127.0.0.1:8080")),
+            ("00000000:0016", Some("0.0.0.0:22")),
+            ("00000000000000000000000001000000:0035", Some("[::1]:53")),
+            ("00000000000000000000000000000000:01BB", Some("[::]:443")),
+            ("0000000000000000FFFF00000100007F:D51E", Some("[::ffff:127.0.0.1]:54558")),
+            ("B80D0120000000000000000001000000:1F90", Some("[2001:db8::1]:8080")),
+            ("0100007F", None),
+            ("0100007:1F90", None),
+            ("ZZ00007F:1F90", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(platform::parse_addr(text).map(|a| a.to_string()).as_deref(), expected, "{}", text);
+        }
+    }
+
+    #[cfg(all(target_os = "linux", target_endian = "little"))]
+    #[test]
+    fn proc_net_tcp6_table() {
+        let table = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:1F90 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 23456 1 0000000000000000 100 0 0 10 0
+   1: 0000000000000000FFFF00000100007F:D51E 0000000000000000FFFF00000100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 23457 1 0000000000000000 20 4 30 10 -1
+   2: 00000000000000000000000001000000:CAE0 00000000000000000000000001000000:1F90 06 00000000:00000000 03:00001192 00000000     0        0 0 3 0000000000000000
+   3: garbage
+";
+        let entries = platform::parse_net_table(table, SocketProto::Tcp);
+        assert_eq!(
+            entries,
+            [
+                (23456, socket(SocketProto::Tcp, "[::]:8080", None, Some(TcpState::Listen))),
+                (
+                    23457,
+                    socket(
+                        SocketProto::Tcp,
+                        "[::ffff:127.0.0.1]:54558",
+                        Some("[::ffff:127.0.0.1]:8080"),
+                        Some(TcpState::Established),
+                    ),
+                ),
+            ]
+        );
+    }
+
+    #[cfg(all(target_os = "linux", target_endian = "little"))]
+    #[test]
+    fn proc_net_udp_has_no_state() {
+        let table = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  512: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 18622 2 0000000000000000 0
+";
+        let entries = platform::parse_net_table(table, SocketProto::Udp);
+        assert_eq!(entries, [(18622, socket(SocketProto::Udp, "127.0.0.53:53", None, None))]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn socket_fds_are_matched_by_inode() {
+        assert_eq!(platform::socket_inode("socket:[12345]"), Some(12345));
+        assert_eq!(platform::socket_inode("pipe:[12345]"), None);
+        assert_eq!(platform::socket_inode("/dev/null"), None);
+        assert_eq!(platform::socket_inode("socket:[]"), None);
+
+        let listener = socket(SocketProto::Tcp, "0.0.0.0:80", None, Some(TcpState::Listen));
+        let dns = socket(SocketProto::Udp, "127.0.0.53:53", None, None);
+        let inode_sockets = HashMap::from([(100, listener.clone()), (200, dns.clone())]);
+        // Two processes share the listener (e.g. after fork); inode 300 is some other socket
+        let fds = [(1, 100), (2, 100), (2, 200), (3, 300)];
+        let mut grouped: Vec<(u32, Vec<SocketInfo>)> = platform::group_by_pid(&inode_sockets, fds).into_iter().collect();
+        grouped.sort_by_key(|(pid, _)| *pid);
+        assert_eq!(grouped, [(1, vec![listener.clone()]), (2, vec![listener, dns])]);
+    }
+
+    #[test]
+    fn kill_owners_skips_unkillable_pids_and_keeps_going() {
+        let own = std::process::id();
+        let results = kill_owners([0, 10, 11, 12, own], |pid| match pid {
+            10 => Err(ProcessError::PermissionDenied { pid }),
+            11 => Err(ProcessError::NoSuchProcess { pid }),
+            _ => Ok(()),
+        });
+        let summary: Vec<String> = results
+            .iter()
+            .map(|r| match r {
+                PortKillResult::Killed { pid } => format!("{} killed", pid),
+                PortKillResult::AlreadyGone { pid } => format!("{} gone", pid),
+                PortKillResult::Skipped { pid, .. } => format!("{} skipped", pid),
+                PortKillResult::Failed { pid, error } => format!("{} failed: {}", pid, error),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "0 skipped".to_string(),
+                "10 failed: Permission denied for process 10".to_string(),
+                "11 gone".to_string(),
+                "12 killed".to_string(),
+                format!("{} skipped", own),
+            ]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProcessError {
+    NoSuchProcess { pid: u32 },
+    PermissionDenied { pid: u32 },
+    UnsupportedSignal { signal: String },
+    Failed { pid: u32, message: String },
+    /// Only raised by the non-Linux fallbacks in `process_limits`.
+    #[cfg(not(target_os = "linux"))]
+    Unsupported { operation: String },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::NoSuchProcess { pid } => write!(f, "No such process: {}", pid),
+            ProcessError::PermissionDenied { pid } => write!(f, "Permission denied for process {}", pid),
+            ProcessError::UnsupportedSignal { signal } => write!(f, "Unsupported signal: {}", signal),
+            ProcessError::Failed { pid, message } => write!(f, "Process {}: {}", pid, message),
+            #[cfg(not(target_os = "linux"))]
+            ProcessError::Unsupported { operation } => write!(f, "{} is not supported on this platform", operation),
+        }
+    }
+}
+
+/// Accepts "term", "TERM", "SIGTERM" and friends. Defaults to SIGTERM when omitted.
+fn parse_signal(name: Option<&str>) -> Result<Signal, ProcessError> {
+    let Some(name) = name else {
+        return Ok(Signal::Term);
+    };
+    let upper = name.trim().to_ascii_uppercase();
+    let short = upper.strip_prefix("SIG").unwrap_or(&upper);
+    match short {
+        "HUP" => Ok(Signal::Hangup),
+        "INT" => Ok(Signal::Interrupt),
+        "QUIT" => Ok(Signal::Quit),
+        "KILL" => Ok(Signal::Kill),
+        "TERM" => Ok(Signal::Term),
+        "STOP" => Ok(Signal::Stop),
+        "CONT" => Ok(Signal::Continue),
+        "USR1" => Ok(Signal::User1),
+        "USR2" => Ok(Signal::User2),
+        _ => Err(ProcessError::UnsupportedSignal { signal: name.to_string() }),
+    }
+}
+
+fn load_processes(pids: ProcessesToUpdate<'_>) -> System {
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(pids, true, ProcessRefreshKind::nothing());
+    sys
+}
+
+/// All descendants of `root` in breadth-first order, excluding `root` itself.
+/// Threads (which sysinfo lists as tasks on Linux) are skipped.
+pub(crate) fn descendants(sys: &System, root: Pid) -> Vec<Pid> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for (pid, process) in sys.processes() {
+        if process.thread_kind().is_some() {
+            continue;
+        }
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(*pid);
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut queue = VecDeque::from([root]);
+    while let Some(pid) = queue.pop_front() {
+        if let Some(kids) = children.get(&pid) {
+            for kid in kids {
+                // Guard against pid reuse producing a cycle
+                if *kid != root && !result.contains(kid) {
+                    result.push(*kid);
+                    queue.push_back(*kid);
+                }
+            }
+        }
+    }
+    result
+}
+
+#[cfg(unix)]
+fn send_signal(sys: &System, pid: u32, signal: Signal) -> Result<(), ProcessError> {
+    let process = sys
+        .process(Pid::from_u32(pid))
+        .ok_or(ProcessError::NoSuchProcess { pid })?;
+    match process.kill_with(signal) {
+        Some(true) => Ok(()),
+        Some(false) => {
+            // sysinfo calls kill(2) directly, so errno still describes the failure
+            let err = std::io::Error::last_os_error();
+            match err.kind() {
+                std::io::ErrorKind::PermissionDenied => Err(ProcessError::PermissionDenied { pid }),
+                _ if err.raw_os_error() == Some(libc::ESRCH) => Err(ProcessError::NoSuchProcess { pid }),
+                _ => Err(ProcessError::Failed { pid, message: err.to_string() }),
+            }
+        }
+        None => Err(ProcessError::UnsupportedSignal { signal: signal.to_string() }),
+    }
+}
+
+/// Sends `signal` to every process in the group led by `pgid`.
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: libc::c_int) -> Result<(), ProcessError> {
+    if unsafe { libc::kill(-(pgid as libc::pid_t), signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    match err.kind() {
+        std::io::ErrorKind::PermissionDenied => Err(ProcessError::PermissionDenied { pid: pgid }),
+        _ if err.raw_os_error() == Some(libc::ESRCH) => Err(ProcessError::NoSuchProcess { pid: pgid }),
+        _ => Err(ProcessError::Failed { pid: pgid, message: err.to_string() }),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn taskkill(pid: u32, args: &[&str]) -> Result<(), ProcessError> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = Command::new("taskkill")
+        .args(args)
+        .args(["/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| ProcessError::Failed { pid, message: e.to_string() })?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // taskkill prints in the console code page, which is GBK on Chinese systems
+    let stderr = match std::str::from_utf8(&output.stderr) {
+        Ok(s) => s.to_string(),
+        Err(_) => encoding_rs::GBK.decode(&output.stderr).0.into_owned(),
+    };
+    // Exit code 128 means the PID was not found
+    if output.status.code() == Some(128) {
+        Err(ProcessError::NoSuchProcess { pid })
+    } else if stderr.contains("Access is denied") || stderr.contains("拒绝访问") {
+        Err(ProcessError::PermissionDenied { pid })
+    } else {
+        Err(ProcessError::Failed { pid, message: stderr.trim().to_string() })
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_threads_suspended(pid: u32, suspend: bool) -> Result<(), ProcessError> {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows_sys::Win32::System::Threading::{OpenThread, ResumeThread, SuspendThread, THREAD_SUSPEND_RESUME};
+
+    // Windows has no SIGSTOP; suspend or resume every thread owned by the process instead.
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Err(ProcessError::Failed { pid, message: std::io::Error::last_os_error().to_string() });
+        }
+
+        let mut entry: THREADENTRY32 = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<THREADENTRY32>() as u32;
+        let mut found = false;
+        let mut result = Ok(());
+
+        let mut ok = Thread32First(snapshot, &mut entry);
+        while ok != 0 {
+            if entry.th32OwnerProcessID == pid {
+                found = true;
+                let thread = OpenThread(THREAD_SUSPEND_RESUME, 0, entry.th32ThreadID);
+                if thread.is_null() {
+                    result = if GetLastError() == ERROR_ACCESS_DENIED {
+                        Err(ProcessError::PermissionDenied { pid })
+                    } else {
+                        Err(ProcessError::Failed { pid, message: std::io::Error::last_os_error().to_string() })
+                    };
+                    break;
+                }
+                let prev = if suspend { SuspendThread(thread) } else { ResumeThread(thread) };
+                CloseHandle(thread);
+                if prev == u32::MAX {
+                    result = Err(ProcessError::Failed { pid, message: std::io::Error::last_os_error().to_string() });
+                    break;
+                }
+            }
+            ok = Thread32Next(snapshot, &mut entry);
+        }
+        CloseHandle(snapshot);
+
+        if !found {
+            return Err(ProcessError::NoSuchProcess { pid });
+        }
+        result
+    }
+}
+
+/// Asks the task led by `pid` to exit: SIGTERM to its process group. Windows has no
+/// equivalent for a windowless child of a GUI app (CTRL_BREAK needs a shared console and
+/// WM_CLOSE needs a window, which the PowerShell running a task lacks), so tasks there are
+/// killed outright and have no grace period.
+#[cfg(unix)]
+pub(crate) fn terminate_group(pid: u32) -> Result<(), ProcessError> {
+    signal_group(pid, libc::SIGTERM)
+}
+
+/// The process table as `kill_task_tree` needs it. Loading it is slow, so take it before
+/// locking anything a task's wait loop uses.
+pub(crate) struct ProcessSnapshot {
+    #[cfg(unix)]
+    sys: System,
+}
+
+impl ProcessSnapshot {
+    pub(crate) fn take() -> Self {
+        ProcessSnapshot {
+            #[cfg(unix)]
+            sys: load_processes(ProcessesToUpdate::All),
+        }
+    }
+}
+
+/// Force-kills a task's processes: the process group led by `pid`, then any descendant
+/// that moved to a group of its own, found by walking ppid in `snapshot`. On Windows, the
+/// process tree. `pid` must not have been reaped yet, or it may name another process by now.
+pub(crate) fn kill_task_tree(pid: u32, snapshot: &ProcessSnapshot) -> Result<(), ProcessError> {
+    #[cfg(unix)]
+    {
+        // Walk the tree first: once the group is dead its members are reparented
+        let strays = descendants(&snapshot.sys, Pid::from_u32(pid));
+        let res = signal_group(pid, libc::SIGKILL);
+        for stray in strays {
+            let _ = send_signal(&snapshot.sys, stray.as_u32(), Signal::Kill);
+        }
+        res
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = snapshot;
+        taskkill(pid, &["/F", "/T"])
+    }
+}
+
+/// Force-kills the process group led by `pgid`. Unlike `kill_task_tree` this is safe after
+/// the leader was reaped, since the id is not reused while any member of the group lives.
+#[cfg(unix)]
+pub(crate) fn kill_group(pgid: u32) -> Result<(), ProcessError> {
+    signal_group(pgid, libc::SIGKILL)
+}
+
+#[tauri::command]
+pub fn kill_process(pid: u32, signal: Option<String>) -> Result<(), ProcessError> {
+    let signal = parse_signal(signal.as_deref())?;
+
+    #[cfg(unix)]
+    {
+        let sys = load_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]));
+        send_signal(&sys, pid, signal)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        match signal {
+            // Without /F taskkill asks the process to close (WM_CLOSE)
+            Signal::Term | Signal::Interrupt | Signal::Hangup | Signal::Quit => taskkill(pid, &[]),
+            Signal::Kill => taskkill(pid, &["/F"]),
+            Signal::Stop => set_threads_suspended(pid, true),
+            Signal::Continue => set_threads_suspended(pid, false),
+            other => Err(ProcessError::UnsupportedSignal { signal: other.to_string() }),
+        }
+    }
+}
+
+/// Kills `pid` and everything it spawned. Returns the PIDs that were targeted.
+#[tauri::command]
+pub fn kill_process_tree(pid: u32) -> Result<Vec<u32>, ProcessError> {
+    let sys = load_processes(ProcessesToUpdate::All);
+    let root = Pid::from_u32(pid);
+    if sys.process(root).is_none() {
+        return Err(ProcessError::NoSuchProcess { pid });
+    }
+    let mut targets = vec![root];
+    targets.extend(descendants(&sys, root));
+
+    #[cfg(unix)]
+    {
+        // Freeze the whole tree first so nothing can fork replacements while we kill it
+        send_signal(&sys, pid, Signal::Stop)?;
+        for child in &targets[1..] {
+            let _ = send_signal(&sys, child.as_u32(), Signal::Stop);
+        }
+        for target in targets.iter().rev() {
+            let res = send_signal(&sys, target.as_u32(), Signal::Kill);
+            if *target == root {
+                res?;
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        taskkill(pid, &["/F", "/T"])?;
+    }
+
+    Ok(targets.into_iter().map(|p| p.as_u32()).collect())
+}
+
+#[tauri::command]
+pub fn suspend_process(pid: u32) -> Result<(), ProcessError> {
+    #[cfg(unix)]
+    {
+        let sys = load_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]));
+        send_signal(&sys, pid, Signal::Stop)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        set_threads_suspended(pid, true)
+    }
+}
+
+#[tauri::command]
+pub fn resume_process(pid: u32) -> Result<(), ProcessError> {
+    #[cfg(unix)]
+    {
+        let sys = load_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]));
+        send_signal(&sys, pid, Signal::Continue)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        set_threads_suspended(pid, false)
+    }
+}
@@ -0,0 +1,184 @@
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tauri::State;
+
+use crate::monitor::MonitorState;
+use crate::process_limits::{self, PriorityLevel, ResourceLimit};
+use crate::{ports, ProcessInfo};
+
+#[derive(Serialize, Clone)]
+pub struct OpenFile {
+    pub fd: u32,
+    pub path: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ProcessDetails {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    pub environ: Vec<String>,
+    pub cwd: Option<String>,
+    pub root: Option<String>,
+    /// The owner's name is `process.user`.
+    pub user_id: Option<String>,
+    pub group_id: Option<String>,
+    pub group: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub start_time: u64,
+    /// Seconds.
+    pub run_time: u64,
+    pub thread_count: Option<usize>,
+    pub virtual_memory: u64,
+    pub disk_read_bytes: u64,
+    pub disk_written_bytes: u64,
+    /// `None` when the platform does not expose it or we lack permission.
+    pub open_files: Option<Vec<OpenFile>>,
+    pub priority: Option<PriorityLevel>,
+    /// Linux only.
+    pub nice: Option<i32>,
+    /// CPU indices the process may run on. Linux only.
+    pub affinity: Option<Vec<usize>>,
+    /// Linux only.
+    pub limits: Option<Vec<ResourceLimit>>,
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::OpenFile;
+    use std::fs;
+
+    pub fn thread_count(pid: u32) -> Option<usize> {
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("Threads:"))
+            .and_then(|v| v.trim().parse().ok())
+    }
+
+    pub fn open_files(pid: u32) -> Option<Vec<OpenFile>> {
+        let entries = fs::read_dir(format!("/proc/{}/fd", pid)).ok()?;
+        let mut files: Vec<OpenFile> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let fd = entry.file_name().to_str()?.parse().ok()?;
+                let path = fs::read_link(entry.path()).ok()?;
+                Some(OpenFile { fd, path: path.to_string_lossy().into_owned() })
+            })
+            .collect();
+        files.sort_by_key(|f| f.fd);
+        Some(files)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::OpenFile;
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+
+    pub fn thread_count(pid: u32) -> Option<usize> {
+        unsafe {
+            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+            if snapshot == INVALID_HANDLE_VALUE {
+                return None;
+            }
+            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+            let mut count = None;
+            let mut ok = Process32FirstW(snapshot, &mut entry);
+            while ok != 0 {
+                if entry.th32ProcessID == pid {
+                    count = Some(entry.cntThreads as usize);
+                    break;
+                }
+                ok = Process32NextW(snapshot, &mut entry);
+            }
+            CloseHandle(snapshot);
+            count
+        }
+    }
+
+    pub fn open_files(_pid: u32) -> Option<Vec<OpenFile>> {
+        // Enumerating another process's handles needs undocumented NT APIs
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::OpenFile;
+
+    pub fn thread_count(_pid: u32) -> Option<usize> {
+        None
+    }
+
+    pub fn open_files(_pid: u32) -> Option<Vec<OpenFile>> {
+        None
+    }
+}
+
+#[tauri::command]
+pub fn get_process_details(pid: u32, monitor: State<'_, MonitorState>) -> Result<ProcessDetails, String> {
+    // Slow on Windows (netstat), so collect before taking the monitor lock
+    let sockets = ports::get_netstat_sockets().remove(&pid).unwrap_or_default();
+
+    let sys_pid = Pid::from_u32(pid);
+    // Any refresh of the sampler's System rewinds this process's CPU times and skews its
+    // next cpu_usage, so load the extra fields into a throwaway one and take CPU from the sampler
+    let cpu_usage = {
+        let sys = monitor.0.system.lock().unwrap();
+        sys.process(sys_pid).map(|p| p.cpu_usage()).unwrap_or(0.0)
+    };
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[sys_pid]),
+        true,
+        ProcessRefreshKind::everything()
+            .without_cpu()
+            .with_environ(UpdateKind::Always)
+            .with_cwd(UpdateKind::Always),
+    );
+
+    let mut details = {
+        let process = sys.process(sys_pid).ok_or_else(|| format!("Process {} not found", pid))?;
+        let disk = process.disk_usage();
+
+        ProcessDetails {
+            process: ProcessInfo {
+                cpu_usage,
+                user: process.user_id().and_then(|uid| monitor.0.user_name(uid)),
+                ..ProcessInfo::from_process(process)
+            },
+            environ: process.environ().iter().map(|s| s.to_string_lossy().into_owned()).collect(),
+            cwd: process.cwd().map(|p| p.to_string_lossy().into_owned()),
+            root: process.root().map(|p| p.to_string_lossy().into_owned()),
+            user_id: process.user_id().map(|uid| (**uid).to_string()),
+            group_id: process.group_id().map(|gid| (*gid).to_string()),
+            group: process.group_id().and_then(|gid| monitor.0.group_name(&gid)),
+            start_time: process.start_time(),
+            run_time: process.run_time(),
+            thread_count: None,
+            virtual_memory: process.virtual_memory(),
+            disk_read_bytes: disk.total_read_bytes,
+            disk_written_bytes: disk.total_written_bytes,
+            open_files: None,
+            priority: None,
+            nice: None,
+            affinity: None,
+            limits: None,
+        }
+    };
+
+    details.process.ports = ports::local_ports(&sockets);
+    details.process.sockets = sockets;
+    details.thread_count = platform::thread_count(pid);
+    details.open_files = platform::open_files(pid);
+    details.priority = process_limits::priority(pid);
+    details.nice = process_limits::nice(pid);
+    details.affinity = process_limits::affinity(pid);
+    details.limits = process_limits::limits(pid);
+
+    Ok(details)
+}
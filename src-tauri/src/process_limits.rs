@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+
+use crate::process_control::ProcessError;
+
+/// Portable scheduling priority. On Linux each level maps to a nice value, on Windows
+/// to the priority class of the same name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityLevel {
+    Idle,
+    BelowNormal,
+    Normal,
+    AboveNormal,
+    High,
+    /// Nice -20 on Linux; real-time scheduling policies are not used.
+    Realtime,
+}
+
+/// Nice values only matter on Linux; Windows maps levels to priority classes instead.
+#[cfg(target_os = "linux")]
+impl PriorityLevel {
+    pub fn nice(self) -> i32 {
+        match self {
+            PriorityLevel::Idle => 19,
+            PriorityLevel::BelowNormal => 10,
+            PriorityLevel::Normal => 0,
+            PriorityLevel::AboveNormal => -5,
+            PriorityLevel::High => -10,
+            PriorityLevel::Realtime => -20,
+        }
+    }
+
+    /// The level whose range contains `nice`.
+    pub fn from_nice(nice: i32) -> Self {
+        match nice {
+            15.. => PriorityLevel::Idle,
+            5..=14 => PriorityLevel::BelowNormal,
+            -4..=4 => PriorityLevel::Normal,
+            -9..=-5 => PriorityLevel::AboveNormal,
+            -19..=-10 => PriorityLevel::High,
+            _ => PriorityLevel::Realtime,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    /// CPU time in seconds.
+    Cpu,
+    /// Largest file the process may create, in bytes.
+    FileSize,
+    DataSize,
+    StackSize,
+    CoreSize,
+    /// Resident set size in bytes (ignored by modern Linux kernels).
+    Rss,
+    Processes,
+    OpenFiles,
+    LockedMemory,
+    /// Virtual address space in bytes.
+    AddressSpace,
+}
+
+/// One rlimit. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimit {
+    pub resource: Resource,
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+#[cfg(target_os = "linux")]
+fn os_error(pid: u32) -> ProcessError {
+    let err = std::io::Error::last_os_error();
+    match err.raw_os_error() {
+        Some(libc::ESRCH) => ProcessError::NoSuchProcess { pid },
+        Some(libc::EPERM) | Some(libc::EACCES) => ProcessError::PermissionDenied { pid },
+        _ => ProcessError::Failed { pid, message: err.to_string() },
+    }
+}
+
+/// Whether `apply_to_command` enforces limits here; on other platforms it does nothing.
+pub const COMMAND_LIMITS_SUPPORTED: bool = cfg!(unix);
+
+/// Restricts processes spawned through `command` to `limits`. Limits can only be lowered:
+/// values above the current hard limit are clamped to it, since raising it needs root.
+#[cfg(unix)]
+pub fn apply_to_command(command: &mut std::process::Command, limits: &[ResourceLimit]) {
+    use std::os::unix::process::CommandExt;
+
+    if limits.is_empty() {
+        return;
+    }
+    let limits = limits.to_vec();
+    // Runs in the forked child before exec, so it must stick to async-signal-safe calls
+    unsafe {
+        command.pre_exec(move || {
+            for limit in &limits {
+                set_own_rlimit(limit)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+pub fn apply_to_command(_command: &mut std::process::Command, _limits: &[ResourceLimit]) {}
+
+#[cfg(unix)]
+fn set_own_rlimit(limit: &ResourceLimit) -> std::io::Result<()> {
+    let to_raw = |value: Option<u64>| value.map_or(libc::RLIM_INFINITY, |v| v as libc::rlim_t);
+
+    macro_rules! call {
+        ($f:ident, $arg:expr) => {
+            match limit.resource {
+                Resource::Cpu => libc::$f(libc::RLIMIT_CPU, $arg),
+                Resource::FileSize => libc::$f(libc::RLIMIT_FSIZE, $arg),
+                Resource::DataSize => libc::$f(libc::RLIMIT_DATA, $arg),
+                Resource::StackSize => libc::$f(libc::RLIMIT_STACK, $arg),
+                Resource::CoreSize => libc::$f(libc::RLIMIT_CORE, $arg),
+                Resource::Rss => libc::$f(libc::RLIMIT_RSS, $arg),
+                Resource::Processes => libc::$f(libc::RLIMIT_NPROC, $arg),
+                Resource::OpenFiles => libc::$f(libc::RLIMIT_NOFILE, $arg),
+                Resource::LockedMemory => libc::$f(libc::RLIMIT_MEMLOCK, $arg),
+                Resource::AddressSpace => libc::$f(libc::RLIMIT_AS, $arg),
+            }
+        };
+    }
+
+    unsafe {
+        let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        if call!(getrlimit, &mut current) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // RLIM_INFINITY is the largest value, so min() also handles "unlimited"
+        let hard = to_raw(limit.hard).min(current.rlim_max);
+        let soft = to_raw(limit.soft).min(hard);
+        let new = libc::rlimit { rlim_cur: soft, rlim_max: hard };
+        if call!(setrlimit, &new) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{os_error, PriorityLevel, Resource, ResourceLimit};
+    use crate::process_control::ProcessError;
+    use std::fs;
+
+    /// Nice values and affinity masks are per thread on Linux, so both are applied to
+    /// every task of the process.
+    fn tasks(pid: u32) -> Result<Vec<u32>, ProcessError> {
+        let entries = fs::read_dir(format!("/proc/{}/task", pid)).map_err(|_| ProcessError::NoSuchProcess { pid })?;
+        Ok(entries
+            .flatten()
+            .filter_map(|e| e.file_name().to_str()?.parse().ok())
+            .collect())
+    }
+
+    pub fn nice(pid: u32) -> Option<i32> {
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // The command name may contain spaces, so count fields from after its closing paren.
+        // "nice" is field 19 of the file; field 3 ("state") is the first one after the paren.
+        let (_, rest) = stat.rsplit_once(')')?;
+        rest.split_whitespace().nth(19 - 3).and_then(|v| v.parse().ok())
+    }
+
+    pub fn priority(pid: u32) -> Option<PriorityLevel> {
+        nice(pid).map(PriorityLevel::from_nice)
+    }
+
+    pub fn set_priority(pid: u32, level: PriorityLevel) -> Result<(), ProcessError> {
+        for tid in tasks(pid)? {
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, level.nice()) } != 0 {
+                let err = os_error(pid);
+                // Threads can exit while we walk the list
+                if !matches!(err, ProcessError::NoSuchProcess { .. }) || tid == pid {
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn affinity(pid: u32) -> Option<Vec<usize>> {
+        unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            if libc::sched_getaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+                return None;
+            }
+            Some((0..libc::CPU_SETSIZE as usize).filter(|cpu| libc::CPU_ISSET(*cpu, &set)).collect())
+        }
+    }
+
+    pub fn set_affinity(pid: u32, cpus: &[usize]) -> Result<(), ProcessError> {
+        if cpus.is_empty() {
+            return Err(ProcessError::Failed { pid, message: "At least one CPU is required".to_string() });
+        }
+        let configured = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_CONF) }.max(1) as usize;
+        if let Some(cpu) = cpus.iter().find(|cpu| **cpu >= configured.min(libc::CPU_SETSIZE as usize)) {
+            return Err(ProcessError::Failed { pid, message: format!("CPU {} does not exist", cpu) });
+        }
+
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in cpus {
+            unsafe { libc::CPU_SET(*cpu, &mut set) };
+        }
+        for tid in tasks(pid)? {
+            let res = unsafe {
+                libc::sched_setaffinity(tid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set)
+            };
+            if res != 0 {
+                let err = os_error(pid);
+                if !matches!(err, ProcessError::NoSuchProcess { .. }) || tid == pid {
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    const LIMIT_NAMES: [(&str, Resource); 10] = [
+        ("Max cpu time", Resource::Cpu),
+        ("Max file size", Resource::FileSize),
+        ("Max data size", Resource::DataSize),
+        ("Max stack size", Resource::StackSize),
+        ("Max core file size", Resource::CoreSize),
+        ("Max resident set", Resource::Rss),
+        ("Max processes", Resource::Processes),
+        ("Max open files", Resource::OpenFiles),
+        ("Max locked memory", Resource::LockedMemory),
+        ("Max address space", Resource::AddressSpace),
+    ];
+
+    /// Parsed from `/proc/<pid>/limits`, which unlike prlimit(2) is readable for any process.
+    pub fn limits(pid: u32) -> Option<Vec<ResourceLimit>> {
+        let content = fs::read_to_string(format!("/proc/{}/limits", pid)).ok()?;
+        let parse = |v: &str| if v == "unlimited" { None } else { v.parse().ok() };
+        Some(
+            content
+                .lines()
+                .filter_map(|line| {
+                    let (name, resource) = LIMIT_NAMES.iter().find(|(name, _)| line.starts_with(name))?;
+                    let mut values = line[name.len()..].split_whitespace();
+                    Some(ResourceLimit {
+                        resource: *resource,
+                        soft: parse(values.next()?),
+                        hard: parse(values.next()?),
+                    })
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{PriorityLevel, ResourceLimit};
+    use crate::process_control::ProcessError;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED, ERROR_INVALID_PARAMETER};
+    use windows_sys::Win32::System::Threading::{
+        GetPriorityClass, OpenProcess, SetPriorityClass, ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS,
+        HIGH_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, PROCESS_QUERY_LIMITED_INFORMATION,
+        PROCESS_SET_INFORMATION, REALTIME_PRIORITY_CLASS,
+    };
+
+    fn last_error(pid: u32) -> ProcessError {
+        match unsafe { GetLastError() } {
+            ERROR_ACCESS_DENIED => ProcessError::PermissionDenied { pid },
+            // OpenProcess reports a PID that doesn't exist as an invalid parameter
+            ERROR_INVALID_PARAMETER => ProcessError::NoSuchProcess { pid },
+            _ => ProcessError::Failed { pid, message: std::io::Error::last_os_error().to_string() },
+        }
+    }
+
+    pub fn nice(_pid: u32) -> Option<i32> {
+        None
+    }
+
+    pub fn priority(pid: u32) -> Option<PriorityLevel> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return None;
+            }
+            let class = GetPriorityClass(handle);
+            CloseHandle(handle);
+            match class {
+                IDLE_PRIORITY_CLASS => Some(PriorityLevel::Idle),
+                BELOW_NORMAL_PRIORITY_CLASS => Some(PriorityLevel::BelowNormal),
+                NORMAL_PRIORITY_CLASS => Some(PriorityLevel::Normal),
+                ABOVE_NORMAL_PRIORITY_CLASS => Some(PriorityLevel::AboveNormal),
+                HIGH_PRIORITY_CLASS => Some(PriorityLevel::High),
+                REALTIME_PRIORITY_CLASS => Some(PriorityLevel::Realtime),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn set_priority(pid: u32, level: PriorityLevel) -> Result<(), ProcessError> {
+        let class = match level {
+            PriorityLevel::Idle => IDLE_PRIORITY_CLASS,
+            PriorityLevel::BelowNormal => BELOW_NORMAL_PRIORITY_CLASS,
+            PriorityLevel::Normal => NORMAL_PRIORITY_CLASS,
+            PriorityLevel::AboveNormal => ABOVE_NORMAL_PRIORITY_CLASS,
+            PriorityLevel::High => HIGH_PRIORITY_CLASS,
+            // Silently downgraded to High by Windows unless we hold SeIncreaseBasePriorityPrivilege
+            PriorityLevel::Realtime => REALTIME_PRIORITY_CLASS,
+        };
+        unsafe {
+            let handle = OpenProcess(PROCESS_SET_INFORMATION, 0, pid);
+            if handle.is_null() {
+                return Err(last_error(pid));
+            }
+            let ok = SetPriorityClass(handle, class);
+            let result = if ok == 0 { Err(last_error(pid)) } else { Ok(()) };
+            CloseHandle(handle);
+            result
+        }
+    }
+
+    pub fn affinity(_pid: u32) -> Option<Vec<usize>> {
+        None
+    }
+
+    pub fn set_affinity(_pid: u32, _cpus: &[usize]) -> Result<(), ProcessError> {
+        Err(ProcessError::Unsupported { operation: "Setting CPU affinity".to_string() })
+    }
+
+    pub fn limits(_pid: u32) -> Option<Vec<ResourceLimit>> {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::{PriorityLevel, ResourceLimit};
+    use crate::process_control::ProcessError;
+
+    pub fn nice(_pid: u32) -> Option<i32> {
+        None
+    }
+
+    pub fn priority(_pid: u32) -> Option<PriorityLevel> {
+        None
+    }
+
+    pub fn set_priority(_pid: u32, _level: PriorityLevel) -> Result<(), ProcessError> {
+        Err(ProcessError::Unsupported { operation: "Changing priority".to_string() })
+    }
+
+    pub fn affinity(_pid: u32) -> Option<Vec<usize>> {
+        None
+    }
+
+    pub fn set_affinity(_pid: u32, _cpus: &[usize]) -> Result<(), ProcessError> {
+        Err(ProcessError::Unsupported { operation: "Setting CPU affinity".to_string() })
+    }
+
+    pub fn limits(_pid: u32) -> Option<Vec<ResourceLimit>> {
+        None
+    }
+}
+
+pub use platform::{affinity, limits, nice, priority};
+
+#[tauri::command]
+pub fn set_process_priority(pid: u32, level: PriorityLevel) -> Result<(), ProcessError> {
+    platform::set_priority(pid, level)
+}
+
+/// Pins `pid` (all of its threads) to the given zero-based CPU indices. Linux only.
+#[tauri::command]
+pub fn set_process_affinity(pid: u32, cpus: Vec<usize>) -> Result<(), ProcessError> {
+    platform::set_affinity(pid, &cpus)
+}
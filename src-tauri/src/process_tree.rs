@@ -0,0 +1,170 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use crate::monitor::MonitorState;
+use crate::ProcessInfo;
+
+#[derive(Serialize, Clone)]
+pub struct ProcessTreeNode {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    /// Memory of this process plus all of its descendants.
+    pub total_memory: u64,
+    /// CPU usage of this process plus all of its descendants.
+    pub total_cpu_usage: f32,
+    pub children: Vec<ProcessTreeNode>,
+}
+
+struct TreeBuilder {
+    by_pid: HashMap<u32, ProcessInfo>,
+    children: HashMap<u32, Vec<u32>>,
+    visited: HashSet<u32>,
+}
+
+impl TreeBuilder {
+    fn new(processes: Vec<ProcessInfo>) -> Self {
+        let by_pid: HashMap<u32, ProcessInfo> = processes.into_iter().map(|p| (p.pid, p)).collect();
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for p in by_pid.values() {
+            if let Some(ppid) = p.ppid {
+                if ppid != p.pid && by_pid.contains_key(&ppid) {
+                    children.entry(ppid).or_default().push(p.pid);
+                }
+            }
+        }
+        for kids in children.values_mut() {
+            kids.sort();
+        }
+        TreeBuilder { by_pid, children, visited: HashSet::new() }
+    }
+
+    /// A process is a root when it has no parent, is its own parent, or its parent is gone (orphan).
+    fn is_root(&self, p: &ProcessInfo) -> bool {
+        match p.ppid {
+            None => true,
+            Some(ppid) => ppid == p.pid || !self.by_pid.contains_key(&ppid),
+        }
+    }
+
+    fn build(&mut self, pid: u32) -> Option<ProcessTreeNode> {
+        // The visited set breaks ppid cycles caused by PID reuse
+        if !self.visited.insert(pid) {
+            return None;
+        }
+        let process = self.by_pid.get(&pid)?.clone();
+        let kids = self.children.get(&pid).cloned().unwrap_or_default();
+        let children: Vec<ProcessTreeNode> = kids.into_iter().filter_map(|kid| self.build(kid)).collect();
+
+        let total_memory = process.memory + children.iter().map(|c| c.total_memory).sum::<u64>();
+        let total_cpu_usage = process.cpu_usage + children.iter().map(|c| c.total_cpu_usage).sum::<f32>();
+        Some(ProcessTreeNode { process, total_memory, total_cpu_usage, children })
+    }
+
+    fn build_forest(mut self) -> Vec<ProcessTreeNode> {
+        let mut pids: Vec<u32> = self.by_pid.keys().copied().collect();
+        pids.sort();
+
+        let roots: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| self.is_root(&self.by_pid[pid]))
+            .collect();
+        let mut forest: Vec<ProcessTreeNode> = roots.into_iter().filter_map(|pid| self.build(pid)).collect();
+
+        // Whatever is still unvisited sits on a parent cycle; cut it at the lowest PID
+        for pid in pids {
+            if let Some(node) = self.build(pid) {
+                forest.push(node);
+            }
+        }
+
+        forest
+    }
+}
+
+fn build_tree(processes: Vec<ProcessInfo>, root_pid: Option<u32>) -> Result<Vec<ProcessTreeNode>, String> {
+    let mut builder = TreeBuilder::new(processes);
+    match root_pid {
+        Some(pid) => builder
+            .build(pid)
+            .map(|node| vec![node])
+            .ok_or_else(|| format!("Process {} not found", pid)),
+        None => Ok(builder.build_forest()),
+    }
+}
+
+/// Returns the process hierarchy, or only the subtree of `root_pid` when given.
+#[tauri::command]
+pub fn get_process_tree(
+    root_pid: Option<u32>,
+    monitor: State<'_, MonitorState>,
+) -> Result<Vec<ProcessTreeNode>, String> {
+    build_tree(monitor.0.processes(), root_pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, ppid: Option<u32>, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            name: format!("p{}", pid),
+            exe: String::new(),
+            cmd: String::new(),
+            memory,
+            cpu_usage: 1.0,
+            status: "Run".to_string(),
+            user: None,
+            ports: Vec::new(),
+            sockets: Vec::new(),
+            window_handle: None,
+        }
+    }
+
+    /// Each node as "pid(child, child)" to compare shapes at a glance.
+    fn shape(nodes: &[ProcessTreeNode]) -> String {
+        nodes
+            .iter()
+            .map(|n| match n.children.is_empty() {
+                true => n.process.pid.to_string(),
+                false => format!("{}({})", n.process.pid, shape(&n.children)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[test]
+    fn orphans_become_roots_and_totals_add_up() {
+        // 3's parent 99 has exited
+        let processes = vec![process(1, None, 10), process(2, Some(1), 20), process(4, Some(2), 5), process(3, Some(99), 7)];
+        let forest = build_tree(processes, None).unwrap();
+        assert_eq!(shape(&forest), "1(2(4)), 3");
+        assert_eq!(forest[0].total_memory, 35);
+        assert_eq!(forest[0].total_cpu_usage, 3.0);
+        assert_eq!(forest[0].children[0].total_memory, 25);
+    }
+
+    #[test]
+    fn self_parented_process_is_a_root() {
+        let forest = build_tree(vec![process(5, Some(5), 1), process(6, Some(5), 1)], None).unwrap();
+        assert_eq!(shape(&forest), "5(6)");
+    }
+
+    #[test]
+    fn two_node_cycle_is_cut_at_the_lowest_pid() {
+        // PID reuse can make two processes each other's parent
+        let forest = build_tree(vec![process(8, Some(7), 1), process(7, Some(8), 1), process(1, None, 1)], None).unwrap();
+        assert_eq!(shape(&forest), "1, 7(8)");
+
+        let subtree = build_tree(vec![process(8, Some(7), 1), process(7, Some(8), 1)], Some(8)).unwrap();
+        assert_eq!(shape(&subtree), "8(7)");
+    }
+
+    #[test]
+    fn unknown_root_is_an_error() {
+        assert!(build_tree(vec![process(1, None, 1)], Some(2)).is_err());
+    }
+}
@@ -0,0 +1,607 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::ProcessInfo;
+
+/// A parse error. `start` and `end` are character offsets into the query string.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryError {
+    pub message: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}..{}", self.message, self.start, self.end)
+    }
+}
+
+fn error<T>(message: impl Into<String>, start: usize, end: usize) -> Result<T, QueryError> {
+    Err(QueryError { message: message.into(), start, end })
+}
+
+/// Process fields that can be filtered and sorted on. Sort keys use the `ProcessInfo`
+/// field names; queries additionally accept a few short aliases (see `Field::from_name`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Pid,
+    Ppid,
+    Name,
+    Exe,
+    Cmd,
+    Memory,
+    CpuUsage,
+    Status,
+    #[serde(alias = "port")]
+    Ports,
+    User,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name.to_ascii_lowercase().as_str() {
+            "pid" => Some(Field::Pid),
+            "ppid" | "parent" => Some(Field::Ppid),
+            "name" => Some(Field::Name),
+            "exe" | "path" => Some(Field::Exe),
+            "cmd" | "command" => Some(Field::Cmd),
+            "memory" | "mem" => Some(Field::Memory),
+            "cpu" | "cpu_usage" => Some(Field::CpuUsage),
+            "status" | "state" => Some(Field::Status),
+            "port" | "ports" => Some(Field::Ports),
+            "user" => Some(Field::User),
+            _ => None,
+        }
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(self, Field::Pid | Field::Ppid | Field::Memory | Field::CpuUsage | Field::Ports)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    /// Case-insensitive substring match.
+    Contains,
+    NotContains,
+}
+
+impl CmpOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "=",
+            CmpOp::Ne => "!=",
+            CmpOp::Gt => ">",
+            CmpOp::Ge => ">=",
+            CmpOp::Lt => "<",
+            CmpOp::Le => "<=",
+            CmpOp::Contains => "~",
+            CmpOp::NotContains => "!~",
+        }
+    }
+
+    fn compare(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (CmpOp::Eq, Some(o)) => o == Ordering::Equal,
+            (CmpOp::Ne, Some(o)) => o != Ordering::Equal,
+            (CmpOp::Gt, Some(o)) => o == Ordering::Greater,
+            (CmpOp::Ge, Some(o)) => o != Ordering::Less,
+            (CmpOp::Lt, Some(o)) => o == Ordering::Less,
+            (CmpOp::Le, Some(o)) => o != Ordering::Greater,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Number(f64),
+    /// Lowercased, since all text matching is case-insensitive.
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare { field: Field, op: CmpOp, value: Value },
+    /// A bare word or string: matches the name or PID, like the old search box.
+    Search(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Str(String),
+    Op(CmpOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '(' | ')' | '=' | '!' | '~' | '<' | '>' | '"' | '\'' | '&' | '|')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+
+        let (kind, len) = match (c, next) {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (TokenKind::LParen, 1),
+            (')', _) => (TokenKind::RParen, 1),
+            ('!', Some('=')) => (TokenKind::Op(CmpOp::Ne), 2),
+            ('!', Some('~')) => (TokenKind::Op(CmpOp::NotContains), 2),
+            ('!', _) => (TokenKind::Not, 1),
+            ('=', Some('=')) => (TokenKind::Op(CmpOp::Eq), 2),
+            ('=', _) => (TokenKind::Op(CmpOp::Eq), 1),
+            ('~', _) => (TokenKind::Op(CmpOp::Contains), 1),
+            ('>', Some('=')) => (TokenKind::Op(CmpOp::Ge), 2),
+            ('>', _) => (TokenKind::Op(CmpOp::Gt), 1),
+            ('<', Some('=')) => (TokenKind::Op(CmpOp::Le), 2),
+            ('<', _) => (TokenKind::Op(CmpOp::Lt), 1),
+            ('&', Some('&')) => (TokenKind::And, 2),
+            ('|', Some('|')) => (TokenKind::Or, 2),
+            ('&' | '|', _) => return error(format!("Unexpected `{}`; use `{}{}`", c, c, c), start, start + 1),
+            ('"' | '\'', _) => {
+                let quote = c;
+                let mut text = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return error("Unterminated string", start, chars.len()),
+                        Some('\\') if j + 1 < chars.len() => {
+                            text.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(&ch) if ch == quote => break,
+                        Some(&ch) => {
+                            text.push(ch);
+                            j += 1;
+                        }
+                    }
+                }
+                (TokenKind::Str(text), j + 1 - i)
+            }
+            _ => {
+                let mut j = i;
+                while j < chars.len() && is_word_char(chars[j]) {
+                    j += 1;
+                }
+                let word: String = chars[i..j].iter().collect();
+                let kind = match word.to_ascii_lowercase().as_str() {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    _ => TokenKind::Word(word),
+                };
+                (kind, j - i)
+            }
+        };
+
+        tokens.push(Token { kind, start, end: start + len });
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+/// Parses "500MB", "1.5g", "2048", "12%". Sizes are binary (1 KB = 1024 bytes).
+fn parse_number(text: &str) -> Option<f64> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let value: f64 = digits.parse().ok()?;
+    let multiplier = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" | "%" => 1.0,
+        "k" | "kb" | "kib" => 1024.0,
+        "m" | "mb" | "mib" => 1024.0 * 1024.0,
+        "g" | "gb" | "gib" => 1024.0 * 1024.0 * 1024.0,
+        "t" | "tb" | "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * multiplier)
+}
+
+/// Recursive-descent parser. `or` binds looser than `and`, which binds looser than `not`;
+/// adjacent terms without an operator are joined with `and`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Length of the input, used to point at the end for "unexpected end" errors.
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.parse_and()?;
+        while self.peek().is_some_and(|t| t.kind == TokenKind::Or) {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::And) => self.pos += 1,
+                Some(TokenKind::Or | TokenKind::RParen) | None => break,
+                // Implicit `and`
+                Some(_) => {}
+            }
+            let right = self.parse_unary()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        if self.peek().is_some_and(|t| t.kind == TokenKind::Not) {
+            self.pos += 1;
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, QueryError> {
+        let Some(token) = self.next() else {
+            return error("Unexpected end of query", self.len, self.len);
+        };
+        match token.kind {
+            TokenKind::LParen => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(inner),
+                    Some(t) => error("Expected `)`", t.start, t.end),
+                    None => error("Missing `)`", token.start, token.end),
+                }
+            }
+            TokenKind::Word(word) => match self.peek().map(|t| t.kind.clone()) {
+                Some(TokenKind::Op(op)) => {
+                    let op_token = self.next().unwrap();
+                    self.parse_comparison(&word, token.start, token.end, op, op_token)
+                }
+                _ => Ok(Expr::Search(word.to_lowercase())),
+            },
+            TokenKind::Str(text) => Ok(Expr::Search(text.to_lowercase())),
+            TokenKind::Op(op) => error(format!("Expected a field before `{}`", op.symbol()), token.start, token.end),
+            TokenKind::RParen => error("Unexpected `)`", token.start, token.end),
+            TokenKind::And | TokenKind::Or | TokenKind::Not => {
+                error("Expected a condition", token.start, token.end)
+            }
+        }
+    }
+
+    fn parse_comparison(
+        &mut self,
+        name: &str,
+        name_start: usize,
+        name_end: usize,
+        op: CmpOp,
+        op_token: Token,
+    ) -> Result<Expr, QueryError> {
+        let field = Field::from_name(name).ok_or_else(|| QueryError {
+            message: format!("Unknown field `{}`", name),
+            start: name_start,
+            end: name_end,
+        })?;
+
+        let value_token = match self.next() {
+            Some(t @ Token { kind: TokenKind::Word(_) | TokenKind::Str(_), .. }) => t,
+            Some(t) => return error(format!("Expected a value after `{}`", op.symbol()), t.start, t.end),
+            None => return error(format!("Expected a value after `{}`", op.symbol()), self.len, self.len),
+        };
+        let (TokenKind::Word(text) | TokenKind::Str(text)) = value_token.kind else {
+            unreachable!()
+        };
+
+        let value = if field.is_numeric() {
+            if matches!(op, CmpOp::Contains | CmpOp::NotContains) {
+                return error(
+                    format!("`{}` only works on text fields", op.symbol()),
+                    op_token.start,
+                    op_token.end,
+                );
+            }
+            let number = parse_number(&text).ok_or_else(|| QueryError {
+                message: format!("Expected a number for `{}`", name),
+                start: value_token.start,
+                end: value_token.end,
+            })?;
+            Value::Number(number)
+        } else {
+            if !matches!(op, CmpOp::Eq | CmpOp::Ne | CmpOp::Contains | CmpOp::NotContains) {
+                return error(
+                    format!("`{}` only works on numeric fields", op.symbol()),
+                    op_token.start,
+                    op_token.end,
+                );
+            }
+            Value::Text(text.to_lowercase())
+        };
+
+        Ok(Expr::Compare { field, op, value })
+    }
+}
+
+/// Filter, sort order and page requested by the process list.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProcessQuery {
+    /// e.g. `name~"node" and memory>500MB and port=3000 or user=root`
+    pub filter: Option<String>,
+    /// Defaults to `pid`.
+    pub sort_by: Option<Field>,
+    pub descending: bool,
+    pub offset: usize,
+    /// All remaining rows when omitted.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct ProcessPage {
+    /// Number of processes matching the filter, before pagination.
+    pub total: usize,
+    pub processes: Vec<ProcessInfo>,
+}
+
+/// A parsed filter. An empty query matches everything.
+#[derive(Debug, Clone)]
+pub struct Query {
+    expr: Option<Expr>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Query, QueryError> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Ok(Query { expr: None });
+        }
+        let mut parser = Parser { tokens, pos: 0, len: input.chars().count() };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return error("Unexpected `)`", token.start, token.end);
+        }
+        Ok(Query { expr: Some(expr) })
+    }
+
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        self.expr.as_ref().is_none_or(|expr| eval(expr, process))
+    }
+
+    /// Whether any comparison in the query looks at `field`.
+    pub fn uses(&self, field: Field) -> bool {
+        fn walk(expr: &Expr, field: Field) -> bool {
+            match expr {
+                Expr::And(a, b) | Expr::Or(a, b) => walk(a, field) || walk(b, field),
+                Expr::Not(inner) => walk(inner, field),
+                Expr::Compare { field: f, .. } => *f == field,
+                Expr::Search(_) => false,
+            }
+        }
+        self.expr.as_ref().is_some_and(|expr| walk(expr, field))
+    }
+}
+
+fn text_of(field: Field, process: &ProcessInfo) -> String {
+    match field {
+        Field::Name => process.name.to_lowercase(),
+        Field::Exe => process.exe.to_lowercase(),
+        Field::Cmd => process.cmd.to_lowercase(),
+        Field::Status => process.status.to_lowercase(),
+        Field::User => process.user.as_deref().unwrap_or_default().to_lowercase(),
+        _ => String::new(),
+    }
+}
+
+fn number_of(field: Field, process: &ProcessInfo) -> Option<f64> {
+    match field {
+        Field::Pid => Some(process.pid as f64),
+        Field::Ppid => process.ppid.map(|p| p as f64),
+        Field::Memory => Some(process.memory as f64),
+        Field::CpuUsage => Some(process.cpu_usage as f64),
+        _ => None,
+    }
+}
+
+fn eval(expr: &Expr, process: &ProcessInfo) -> bool {
+    match expr {
+        Expr::And(a, b) => eval(a, process) && eval(b, process),
+        Expr::Or(a, b) => eval(a, process) || eval(b, process),
+        Expr::Not(inner) => !eval(inner, process),
+        Expr::Search(text) => process.name.to_lowercase().contains(text) || process.pid.to_string().contains(text),
+        Expr::Compare { field: Field::Ports, op, value: Value::Number(n) } => {
+            let hit = |p: &u16| op.compare((*p as f64).partial_cmp(n));
+            // A process matches `port!=X` only if none of its ports is X
+            match op {
+                CmpOp::Ne => !process.ports.iter().any(|p| CmpOp::Eq.compare((*p as f64).partial_cmp(n))),
+                _ => process.ports.iter().any(hit),
+            }
+        }
+        Expr::Compare { field, op, value: Value::Number(n) } => {
+            op.compare(number_of(*field, process).and_then(|v| v.partial_cmp(n)))
+        }
+        Expr::Compare { field, op, value: Value::Text(text) } => {
+            let actual = text_of(*field, process);
+            match op {
+                CmpOp::Eq => actual == *text,
+                CmpOp::Ne => actual != *text,
+                CmpOp::Contains => actual.contains(text.as_str()),
+                CmpOp::NotContains => !actual.contains(text.as_str()),
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Orders processes by `field`; processes without a value (e.g. no parent) sort first.
+pub fn compare_by(field: Field, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+    if field.is_numeric() {
+        let key = |p: &ProcessInfo| match field {
+            Field::Ports => p.ports.first().map(|port| *port as f64),
+            _ => number_of(field, p),
+        };
+        key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal)
+    } else {
+        text_of(field, a).cmp(&text_of(field, b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, memory: u64, ports: &[u16]) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: None,
+            name: name.to_string(),
+            exe: String::new(),
+            cmd: String::new(),
+            memory,
+            cpu_usage: 0.0,
+            status: "Run".to_string(),
+            user: None,
+            ports: ports.to_vec(),
+            sockets: Vec::new(),
+            window_handle: None,
+        }
+    }
+
+    fn matches(query: &str, process: &ProcessInfo) -> bool {
+        Query::parse(query).unwrap().matches(process)
+    }
+
+    fn parse_error(query: &str) -> (String, usize, usize) {
+        let err = Query::parse(query).unwrap_err();
+        (err.message, err.start, err.end)
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        // name=a or (name=b and pid=2)
+        assert!(matches("name=a or name=b and pid=2", &process(1, "a", 0, &[])));
+        assert!(!matches("name=a or name=b and pid=2", &process(1, "b", 0, &[])));
+        assert!(!matches("(name=a or name=b) and pid=2", &process(1, "a", 0, &[])));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        // (not name=a) and pid=1
+        assert!(matches("not name=a and pid=1", &process(1, "b", 0, &[])));
+        assert!(!matches("not name=a and pid=1", &process(2, "b", 0, &[])));
+        assert!(matches("!(name=a and pid=1)", &process(2, "b", 0, &[])));
+    }
+
+    #[test]
+    fn adjacent_terms_are_anded() {
+        assert!(matches("name~fire pid>10", &process(20, "firefox", 0, &[])));
+        assert!(!matches("name~fire pid>10", &process(5, "firefox", 0, &[])));
+        // (name=a and pid=2) or pid=1
+        assert!(matches("name=a pid=2 or pid=1", &process(1, "b", 0, &[])));
+        assert!(!matches("name=a pid=2 or pid=1", &process(3, "a", 0, &[])));
+    }
+
+    #[test]
+    fn sizes_are_binary_units() {
+        assert_eq!(parse_number("500MB"), Some(500.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_number("1.5g"), Some(1.5 * 1024.0 * 1024.0 * 1024.0));
+        assert_eq!(parse_number("4KiB"), Some(4096.0));
+        assert_eq!(parse_number("12%"), Some(12.0));
+        assert_eq!(parse_number("2048"), Some(2048.0));
+        assert_eq!(parse_number("5xb"), None);
+
+        let limit = 500 * 1024 * 1024;
+        assert!(matches("memory>500MB", &process(1, "a", limit + 1, &[])));
+        assert!(!matches("memory>500MB", &process(1, "a", limit, &[])));
+        assert!(matches("mem<=500mb", &process(1, "a", limit, &[])));
+    }
+
+    #[test]
+    fn port_ne_requires_no_port_to_match() {
+        assert!(!matches("port!=3000", &process(1, "a", 0, &[3000, 8080])));
+        assert!(matches("port!=3000", &process(1, "a", 0, &[8080])));
+        assert!(matches("port!=3000", &process(1, "a", 0, &[])));
+        assert!(matches("port=3000", &process(1, "a", 0, &[80, 3000])));
+        assert!(!matches("not port=3000", &process(1, "a", 0, &[80, 3000])));
+    }
+
+    #[test]
+    fn uses_finds_fields_anywhere_in_the_tree() {
+        let query = Query::parse("name=a or not (pid>1 and port=80)").unwrap();
+        assert!(query.uses(Field::Ports));
+        assert!(query.uses(Field::Name));
+        assert!(!query.uses(Field::Memory));
+        assert!(!Query::parse("8080").unwrap().uses(Field::Ports));
+        assert!(!Query::parse("").unwrap().uses(Field::Ports));
+    }
+
+    #[test]
+    fn unterminated_string_spans_to_the_end() {
+        assert_eq!(parse_error("name=\"fire"), ("Unterminated string".to_string(), 5, 10));
+        assert_eq!(parse_error("name='a\\'"), ("Unterminated string".to_string(), 5, 9));
+    }
+
+    #[test]
+    fn unknown_field_points_at_the_name() {
+        assert_eq!(parse_error("foo=1"), ("Unknown field `foo`".to_string(), 0, 3));
+        // Offsets count characters, not bytes
+        assert_eq!(parse_error("名前 bogus>1"), ("Unknown field `bogus`".to_string(), 3, 8));
+    }
+
+    #[test]
+    fn bad_operator_points_at_the_operator() {
+        assert_eq!(parse_error("name>=a"), ("`>=` only works on numeric fields".to_string(), 4, 6));
+        assert_eq!(parse_error("pid~1"), ("`~` only works on text fields".to_string(), 3, 4));
+        assert_eq!(parse_error("a & b"), ("Unexpected `&`; use `&&`".to_string(), 2, 3));
+    }
+
+    #[test]
+    fn trailing_input_points_at_the_leftover() {
+        assert_eq!(parse_error("pid=1)"), ("Unexpected `)`".to_string(), 5, 6));
+        assert_eq!(parse_error("(pid=1))"), ("Unexpected `)`".to_string(), 7, 8));
+    }
+}
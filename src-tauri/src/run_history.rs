@@ -0,0 +1,229 @@
+use chrono::{DateTime, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::scheduler::SchedulerState;
+use crate::task_output::TaskOutput;
+use crate::timer_wheel::Trigger;
+
+/// Runs kept per task; older ones are dropped along with their logs.
+const MAX_RUNS_PER_TASK: usize = 100;
+/// Runs older than this are dropped whatever the count.
+const MAX_RUN_AGE: TimeDelta = TimeDelta::days(30);
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Why a task run was started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Cron,
+    Manual,
+    CatchUp,
+    /// Started by a process watch rule.
+    Watch,
+}
+
+impl From<Trigger> for RunTrigger {
+    fn from(trigger: Trigger) -> Self {
+        match trigger {
+            Trigger::Cron => RunTrigger::Cron,
+            Trigger::CatchUp => RunTrigger::CatchUp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Success,
+    /// Exited with a non-zero code or was killed.
+    Failed,
+    /// The command could not be started or waited on.
+    Error,
+    /// Stopped after running past the task's `timeout_secs`.
+    TimedOut,
+    /// aTools exited while the run was in progress.
+    Interrupted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub run_id: String,
+    pub task_id: String,
+    pub trigger: RunTrigger,
+    /// RFC 3339 local time.
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub duration_ms: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub status: RunStatus,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Paging {
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct RunPage {
+    /// Runs recorded for the task before paging.
+    pub total: usize,
+    /// Newest first.
+    pub runs: Vec<RunRecord>,
+}
+
+/// Run records in `task_runs.json`, with each run's full output in `run_logs/<run id>.jsonl`
+/// (one `TaskOutput` per line).
+pub struct RunHistory {
+    runs: Mutex<Vec<RunRecord>>,
+    index_path: PathBuf,
+    log_dir: PathBuf,
+}
+
+impl RunHistory {
+    pub fn new(data_dir: &Path) -> Self {
+        let index_path = data_dir.join("task_runs.json");
+        let log_dir = data_dir.join("run_logs");
+        let _ = fs::create_dir_all(&log_dir);
+
+        let mut runs: Vec<RunRecord> = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        // Same as the "Running..." reset in tasks.json
+        for run in runs.iter_mut().filter(|r| r.status == RunStatus::Running) {
+            run.status = RunStatus::Interrupted;
+        }
+
+        RunHistory {
+            runs: Mutex::new(runs),
+            index_path,
+            log_dir,
+        }
+    }
+
+    fn save(&self, runs: &[RunRecord]) {
+        let content = serde_json::to_string_pretty(runs).unwrap_or_default();
+        let _ = fs::write(&self.index_path, content);
+    }
+
+    fn log_path(&self, run_id: &str) -> PathBuf {
+        self.log_dir.join(format!("{}.jsonl", run_id))
+    }
+
+    /// Records a new run as running and creates its log file.
+    pub fn start(&self, task_id: &str, trigger: RunTrigger) -> (RunRecord, Option<File>) {
+        let record = RunRecord {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            task_id: task_id.to_string(),
+            trigger,
+            started_at: Local::now().to_rfc3339(),
+            ended_at: None,
+            duration_ms: None,
+            exit_code: None,
+            status: RunStatus::Running,
+        };
+        let log = File::create(self.log_path(&record.run_id)).ok();
+
+        let mut runs = self.runs.lock().unwrap();
+        runs.push(record.clone());
+        self.save(&runs);
+        (record, log)
+    }
+
+    /// Stores how the run ended and applies the retention limits.
+    pub fn finish(&self, run_id: &str, status: RunStatus, exit_code: Option<i32>) {
+        let now = Local::now();
+        let mut runs = self.runs.lock().unwrap();
+        if let Some(run) = runs.iter_mut().find(|r| r.run_id == run_id) {
+            let started = DateTime::parse_from_rfc3339(&run.started_at).ok();
+            run.duration_ms = started.map(|s| (now - s.with_timezone(&Local)).num_milliseconds().max(0) as u64);
+            run.ended_at = Some(now.to_rfc3339());
+            run.exit_code = exit_code;
+            run.status = status;
+        }
+        self.prune(&mut runs, now);
+        self.save(&runs);
+    }
+
+    fn prune(&self, runs: &mut Vec<RunRecord>, now: DateTime<Local>) {
+        let cutoff = now - MAX_RUN_AGE;
+        let mut kept_per_task = HashMap::new();
+        // Walk newest first so the count keeps the latest runs
+        let mut keep = vec![true; runs.len()];
+        for (i, run) in runs.iter().enumerate().rev() {
+            let kept = kept_per_task.entry(run.task_id.as_str()).or_insert(0);
+            let too_old = DateTime::parse_from_rfc3339(&run.started_at).is_ok_and(|s| s < cutoff);
+            if run.status != RunStatus::Running && (*kept >= MAX_RUNS_PER_TASK || too_old) {
+                keep[i] = false;
+            } else {
+                *kept += 1;
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        runs.retain(|run| {
+            let kept = keep.next().unwrap_or(true);
+            if !kept {
+                let _ = fs::remove_file(self.log_path(&run.run_id));
+            }
+            kept
+        });
+    }
+
+    /// Drops every run of a deleted task.
+    pub fn remove_task(&self, task_id: &str) {
+        let mut runs = self.runs.lock().unwrap();
+        runs.retain(|run| {
+            if run.task_id != task_id {
+                return true;
+            }
+            let _ = fs::remove_file(self.log_path(&run.run_id));
+            false
+        });
+        self.save(&runs);
+    }
+
+    pub fn page(&self, task_id: &str, paging: &Paging) -> RunPage {
+        let runs = self.runs.lock().unwrap();
+        let matching: Vec<&RunRecord> = runs.iter().rev().filter(|r| r.task_id == task_id).collect();
+        RunPage {
+            total: matching.len(),
+            runs: matching
+                .into_iter()
+                .skip(paging.offset)
+                .take(paging.limit.unwrap_or(DEFAULT_PAGE_SIZE))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn output(&self, run_id: &str) -> Result<Vec<TaskOutput>, String> {
+        if !self.runs.lock().unwrap().iter().any(|r| r.run_id == run_id) {
+            return Err(format!("Run {} not found", run_id));
+        }
+        let content = fs::read_to_string(self.log_path(run_id))
+            .map_err(|e| format!("Failed to read output of run {}: {}", run_id, e))?;
+        // A line cut short by a crash is skipped
+        Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+}
+
+/// Runs of `task_id`, newest first.
+#[tauri::command]
+pub fn get_task_runs(task_id: String, paging: Option<Paging>, state: State<'_, SchedulerState>) -> RunPage {
+    state.0.history.page(&task_id, &paging.unwrap_or_default())
+}
+
+/// The full output of one run, in the order it was printed.
+#[tauri::command]
+pub fn get_run_output(run_id: String, state: State<'_, SchedulerState>) -> Result<Vec<TaskOutput>, String> {
+    state.0.history.output(&run_id)
+}
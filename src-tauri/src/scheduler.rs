@@ -1,17 +1,22 @@
-use std::fs;
+use std::fs::{self, File};
 use std::path::PathBuf;
-use std::process::{Command, Stdio, Child};
+use std::process::{Command, Stdio, Child, ExitStatus};
+use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use std::io::{Read, BufReader};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::io::{BufWriter, Read, Write};
 use std::collections::HashMap;
-use cron::Schedule;
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 use tauri::{AppHandle, Manager, Emitter, State};
 
+use crate::process_control;
+use crate::process_limits::{self, ResourceLimit};
+use crate::task_output::{self, OutputEncoding, OutputStream, TaskOutput};
+use crate::run_history::{RunHistory, RunStatus, RunTrigger};
+use crate::timer_wheel::{self, MisfirePolicy, ScheduleSpec, SystemClock};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
     pub id: String,
@@ -21,20 +26,42 @@ pub struct Task {
     pub enabled: bool,
     pub last_run: Option<String>, 
     pub last_result: Option<String>,
+    /// rlimits applied to the spawned command (Unix only).
+    #[serde(default)]
+    pub limits: Vec<ResourceLimit>,
+    /// What to do about runs missed while the machine slept or aTools was closed.
+    #[serde(default)]
+    pub misfire_policy: MisfirePolicy,
+    /// How stdout/stderr bytes are decoded, e.g. `auto`, `utf-8`, `gbk`, `utf-16le`.
+    #[serde(default)]
+    pub output_encoding: OutputEncoding,
+    /// Stop the run after this long. `None` or 0 lets it run forever.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// How long a timed-out run gets to exit after SIGTERM before its process group is
+    /// killed. Ignored on Windows, where runs are killed at the timeout.
+    #[serde(default = "default_grace_secs")]
+    pub grace_secs: u64,
+}
+
+fn default_grace_secs() -> u64 {
+    10
 }
 
 pub struct Scheduler {
     pub tasks: Arc<Mutex<Vec<Task>>>,
-    pub running_tasks: Arc<Mutex<HashMap<String, Arc<Mutex<Child>>>>>,
+    pub running_tasks: Arc<RunningTasks>,
     pub app_handle: AppHandle,
     pub data_path: PathBuf,
+    pub history: RunHistory,
+    wake: Sender<()>,
 }
 
 #[derive(Clone)]
 pub struct SchedulerState(pub Arc<Scheduler>);
 
 impl Scheduler {
-    pub fn new(app_handle: AppHandle) -> Self {
+    pub fn new(app_handle: AppHandle, wake: Sender<()>) -> Self {
         let data_dir = app_handle.path().app_data_dir().unwrap_or_else(|_| PathBuf::from("data"));
         if !data_dir.exists() {
             let _ = fs::create_dir_all(&data_dir);
@@ -62,6 +89,8 @@ impl Scheduler {
             running_tasks: Arc::new(Mutex::new(HashMap::new())),
             app_handle,
             data_path,
+            history: RunHistory::new(&data_dir),
+            wake,
         }
     }
 
@@ -70,82 +99,68 @@ impl Scheduler {
         let content = serde_json::to_string_pretty(&*tasks).unwrap_or_default();
         let _ = fs::write(&self.data_path, content);
     }
+
+    /// Wakes the scheduler thread so it picks up added, edited or deleted tasks.
+    pub fn notify(&self) {
+        let _ = self.wake.send(());
+    }
+
+    /// Every enabled task that has a schedule.
+    fn schedules(&self) -> Vec<ScheduleSpec> {
+        let tasks = self.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .filter(|t| t.enabled && !t.cron_expr.is_empty())
+            .map(|t| ScheduleSpec {
+                id: t.id.clone(),
+                cron_expr: t.cron_expr.clone(),
+                misfire: t.misfire_policy,
+                last_run: t
+                    .last_run
+                    .as_deref()
+                    .and_then(|lr| DateTime::parse_from_rfc3339(lr).ok())
+                    .map(|lr| lr.with_timezone(&Local)),
+            })
+            .collect()
+    }
 }
 
 pub fn init(app_handle: AppHandle) -> SchedulerState {
-    let scheduler = Scheduler::new(app_handle.clone());
+    let (wake_tx, wake_rx) = mpsc::channel();
+    let scheduler = Scheduler::new(app_handle.clone(), wake_tx);
     let state = SchedulerState(Arc::new(scheduler));
     
     // Start background thread
     let thread_state = state.clone();
     thread::spawn(move || {
-        loop {
-            thread::sleep(Duration::from_secs(1)); // Check every second
-            
-            let now = Local::now();
-            let mut tasks_to_run = Vec::new(); // (id)
-
-            {
-                let scheduler = &thread_state.0;
-                let mut tasks = scheduler.tasks.lock().unwrap();
-                
-                for task in tasks.iter_mut() {
-                    if !task.enabled || task.cron_expr.is_empty() {
-                        continue;
-                    }
-
-                    if let Ok(schedule) = Schedule::from_str(&task.cron_expr) {
-                        // Check if we hit a schedule time in the last 2 seconds (to be safe with sleep drift)
-                        let check_start = now - chrono::Duration::seconds(2);
-                        let next_occurrence = schedule.after(&check_start).next();
-                        
-                        if let Some(time) = next_occurrence {
-                            // If the scheduled time is <= now and > now - 2s
-                            if time <= now {
-                                 // Check debounce
-                                 let last_run_ok = if let Some(lr) = &task.last_run {
-                                     let lr_dt = DateTime::parse_from_rfc3339(lr).ok();
-                                     if let Some(lrdt) = lr_dt {
-                                          let diff = (now - lrdt.with_timezone(&Local)).num_seconds();
-                                          diff > 1 
-                                     } else { true }
-                                 } else { true };
-                                 
-                                 if last_run_ok {
-                                     tasks_to_run.push(task.id.clone());
-                                     // Update last_run immediately to prevent double-add in very slow loops?
-                                     // No, we update in run_task_internal.
-                                     // Actually, if we sleep 1s, next iteration might see the same 'time' if update hasn't happened.
-                                     // But run_task_internal writes last_run.
-                                 }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Execute outside lock to avoid blocking
-            for id in tasks_to_run {
+        timer_wheel::run(
+            &SystemClock,
+            &wake_rx,
+            || thread_state.0.schedules(),
+            |id, runs| {
                 let thread_state_clone = thread_state.clone();
-                let task_id = id.clone();
+                let task_id = id.to_string();
+                // Catch-up runs of one task go one after another
                 thread::spawn(move || {
-                    run_task_internal(&thread_state_clone, &task_id);
+                    for trigger in runs {
+                        run_task_internal(&thread_state_clone, &task_id, trigger.into());
+                    }
                 });
-            }
-        }
+            },
+        );
     });
 
     state
 }
 
-fn run_task_internal(state: &SchedulerState, id: &str) {
+pub(crate) fn run_task_internal(state: &SchedulerState, id: &str, trigger: RunTrigger) {
     let scheduler = &state.0;
     
-    let (cmd_str, _task_name) = {
+    let task = {
         let mut tasks = scheduler.tasks.lock().unwrap();
         if let Some(t) = tasks.iter_mut().find(|t| t.id == id) {
              t.last_result = Some("Running...".to_string());
-             (t.command.clone(), t.name.clone())
+             t.clone()
         } else {
             return;
         }
@@ -155,7 +170,17 @@ fn run_task_internal(state: &SchedulerState, id: &str) {
     let _ = scheduler.app_handle.emit("task-updated", ());
 
     // Execution
-    let result = execute_streaming(scheduler, id, &cmd_str);
+    let (run, log) = scheduler.history.start(id, trigger);
+    let output = Arc::new(RunOutput {
+        run_id: run.run_id.clone(),
+        task_id: task.id.clone(),
+        app_handle: scheduler.app_handle.clone(),
+        text: Mutex::new(String::new()),
+        log: Mutex::new(log.map(BufWriter::new)),
+    });
+    let outcome = execute_streaming(scheduler, &task, output.clone());
+    output.finish();
+    scheduler.history.finish(&run.run_id, outcome.status, outcome.exit_code);
     let now_str = Local::now().to_rfc3339();
 
     // Update state
@@ -163,7 +188,7 @@ fn run_task_internal(state: &SchedulerState, id: &str) {
         let mut tasks = scheduler.tasks.lock().unwrap();
         if let Some(t) = tasks.iter_mut().find(|t| t.id == id) {
             t.last_run = Some(now_str);
-            t.last_result = Some(result);
+            t.last_result = Some(outcome.last_result);
         }
     } // Lock released
 
@@ -173,16 +198,76 @@ fn run_task_internal(state: &SchedulerState, id: &str) {
     let _ = scheduler.app_handle.emit("task-updated", ());
 }
 
-fn execute_streaming(scheduler: &Scheduler, id: &str, cmd_str: &str) -> String {
+/// Output collected from a run: sent to the frontend line by line, kept in memory for
+/// `last_result` and written in full to the run's log file.
+struct RunOutput {
+    run_id: String,
+    task_id: String,
+    app_handle: AppHandle,
+    text: Mutex<String>,
+    log: Mutex<Option<BufWriter<File>>>,
+}
+
+impl RunOutput {
+    fn push(&self, stream: OutputStream, text: &str) {
+        let line = TaskOutput {
+            run_id: self.run_id.clone(),
+            task_id: self.task_id.clone(),
+            stream,
+            ts: chrono::Utc::now().timestamp_millis(),
+            text: text.to_string(),
+        };
+        {
+            let mut acc = self.text.lock().unwrap();
+            acc.push_str(text);
+            acc.push('\n');
+        }
+        // One JSON object per line, read back by `get_run_output`
+        if let Some(log) = self.log.lock().unwrap().as_mut() {
+            if serde_json::to_writer(&mut *log, &line).is_ok() {
+                let _ = log.write_all(b"\n");
+            }
+        }
+        let _ = self.app_handle.emit("task-output", &line);
+    }
+
+    /// Writes out what is still buffered for the log file.
+    fn finish(&self) {
+        if let Some(log) = self.log.lock().unwrap().as_mut() {
+            let _ = log.flush();
+        }
+    }
+}
+
+/// The last `max` bytes of `text` or a little less, so the cut lands on a character boundary.
+fn tail(text: &str, max: usize) -> &str {
+    let mut start = text.len().saturating_sub(max);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// How a run ended.
+struct RunOutcome {
+    /// Summary shown as the task's `last_result`.
+    last_result: String,
+    status: RunStatus,
+    exit_code: Option<i32>,
+}
+
+/// The command that runs a task: PowerShell on Windows, `sh -c` elsewhere, in a
+/// process group of its own so the task can be stopped along with everything it starts.
+pub(crate) fn shell_command(cmd_str: &str, limits: &[ResourceLimit]) -> Command {
     let mut command;
 
     #[cfg(target_os = "windows")]
     {
         // Use PowerShell without forcing UTF-8 on the console or pipe.
         // This ensures native commands (which output in system encoding, e.g. GBK) are transmitted as-is.
-        // We will decode the output on the Rust side using encoding_rs.
+        // The output is decoded on the Rust side according to the task's output_encoding.
         command = Command::new("powershell");
-        command.args(&["-NoProfile", "-NonInteractive", "-Command", cmd_str]);
+        command.args(["-NoProfile", "-NonInteractive", "-Command", cmd_str]);
 
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
@@ -192,7 +277,144 @@ fn execute_streaming(scheduler: &Scheduler, id: &str, cmd_str: &str) -> String {
     #[cfg(not(target_os = "windows"))]
     {
         command = Command::new("sh");
-        command.args(&["-c", cmd_str]);
+        command.args(["-c", cmd_str]);
+    }
+
+    // Own process group, so stopping the task reaches everything it started
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    process_limits::apply_to_command(&mut command, limits);
+
+    command
+}
+
+/// Kills a running task's process and everything it started.
+fn kill_task_process(child: &Mutex<Child>) {
+    // The scan is slow, so take it before the lock the run's wait loop polls
+    let snapshot = process_control::ProcessSnapshot::take();
+    let mut child = child.lock().unwrap();
+    if let Ok(None) = child.try_wait() {
+        let _ = process_control::kill_task_tree(child.id(), &snapshot);
+        let _ = child.kill();
+    } else {
+        // Reaped, so its PID may name another process by now; its group can't
+        #[cfg(unix)]
+        let _ = process_control::kill_group(child.id());
+    }
+}
+
+/// The child process of each running task, by task id.
+type RunningTasks = Mutex<HashMap<String, Arc<Mutex<Child>>>>;
+
+/// Stops the run of task `id`, if there is one.
+fn stop_running(running_tasks: &RunningTasks, id: &str) {
+    let child = running_tasks.lock().unwrap().get(id).cloned();
+    if let Some(child) = child {
+        kill_task_process(&child);
+    }
+}
+
+/// How long a killed run waits for its output pipes to close. A process that left the
+/// task's process group (or any process on Windows, once the shell is gone) can hold them
+/// open after the kill; its output is then abandoned rather than keeping the run going.
+const PIPE_DRAIN_AFTER_KILL: Duration = Duration::from_secs(5);
+
+/// How `wait_for_run` saw a run end.
+struct RunExit {
+    status: std::io::Result<ExitStatus>,
+    timed_out: bool,
+}
+
+/// Waits until the shell has exited and `pumps` are done with its output, which also
+/// takes whatever it left in the background (`server &`) holding the pipes to exit.
+/// Until then the run can be stopped, and after `timeout` it is asked to exit and killed
+/// `grace` later. `report` adds a line to the run's stderr; `tick` is called every second.
+fn wait_for_run(
+    child: &Mutex<Child>,
+    pumps: Vec<JoinHandle<()>>,
+    timeout: Option<Duration>,
+    grace: Duration,
+    report: &dyn Fn(&str),
+    tick: &mut dyn FnMut(),
+) -> RunExit {
+    // Leads the task's process group, see `shell_command`
+    #[cfg(unix)]
+    let pid = child.lock().unwrap().id();
+    let started = Instant::now();
+    let mut status = None;
+    // When the graceful signal went out, and when the group was killed
+    let mut terminated_at: Option<Instant> = None;
+    let mut killed_at: Option<Instant> = None;
+    let mut polls = 0;
+
+    loop {
+        if status.is_none() {
+            status = child.lock().unwrap().try_wait().transpose();
+        }
+        if status.is_some() {
+            if pumps.iter().all(JoinHandle::is_finished) {
+                break;
+            }
+            if killed_at.is_some_and(|at| at.elapsed() >= PIPE_DRAIN_AFTER_KILL) {
+                report("Stopped waiting for output still held open by a process the task started");
+                break;
+            }
+        }
+
+        match terminated_at {
+            None if timeout.is_some_and(|timeout| started.elapsed() >= timeout) => {
+                #[cfg(unix)]
+                match process_control::terminate_group(pid) {
+                    // Exited in the meantime; the next poll picks that up
+                    Ok(()) | Err(process_control::ProcessError::NoSuchProcess { .. }) => {}
+                    // Nothing will act on the request, so don't wait out the grace
+                    Err(e) => {
+                        report(&format!("Could not stop the task gracefully ({}), killing it", e));
+                        kill_task_process(child);
+                        killed_at = Some(Instant::now());
+                    }
+                }
+                // No way to ask a windowless task to exit, see `terminate_group`
+                #[cfg(not(unix))]
+                {
+                    kill_task_process(child);
+                    killed_at = Some(Instant::now());
+                }
+                terminated_at = Some(Instant::now());
+            }
+            Some(at) if killed_at.is_none() && at.elapsed() >= grace => {
+                kill_task_process(child);
+                killed_at = Some(Instant::now());
+            }
+            _ => {}
+        }
+
+        thread::sleep(Duration::from_millis(200));
+        polls += 1;
+        if polls % 5 == 0 {
+            tick();
+        }
+    }
+
+    // Pumps still running were given up on above and are left to finish on their own
+    for pump in pumps.into_iter().filter(JoinHandle::is_finished) {
+        let _ = pump.join();
+    }
+    RunExit {
+        status: status.expect("the loop only ends once the shell has exited"),
+        timed_out: terminated_at.is_some(),
+    }
+}
+
+fn execute_streaming(scheduler: &Scheduler, task: &Task, output: Arc<RunOutput>) -> RunOutcome {
+    let mut command = shell_command(&task.command, &task.limits);
+    // Saving such a task is refused, but one from an older tasks.json still runs
+    if !task.limits.is_empty() && !process_limits::COMMAND_LIMITS_SUPPORTED {
+        output.push(OutputStream::Stderr, "Resource limits were not applied: not supported on this platform");
     }
 
     // Pipe outputs
@@ -205,251 +427,107 @@ fn execute_streaming(scheduler: &Scheduler, id: &str, cmd_str: &str) -> String {
             let stderr = child.stderr.take();
             
             // Store child to allow killing
+            let child = Arc::new(Mutex::new(child));
             {
                 let mut running = scheduler.running_tasks.lock().unwrap();
-                running.insert(id.to_string(), Arc::new(Mutex::new(child)));
+                running.insert(task.id.clone(), child.clone());
             }
             
-            let _output_acc = String::new();
-            let app_handle = scheduler.app_handle.clone();
-            let task_id = id.to_string();
-            
-            // We can only block on one thing easily in this thread.
-            // Spawning separate threads for stdout/stderr?
-            // Or just read one then the other? (Deadlock risk if buffer full)
-            // Proper way: threads.
-            
-            let output_arc = Arc::new(Mutex::new(String::new()));
-            let output_clone1 = output_arc.clone();
-            let output_clone2 = output_arc.clone();
-            let app_handle_clone = app_handle.clone();
-            let task_id_t2 = task_id.clone();
-            let task_id_t1 = task_id.clone();
-            
-            let t1 = if let Some(out) = stdout {
+            // One thread per pipe so neither can fill up and block the child
+            let spawn_pump = |pipe: Option<Box<dyn Read + Send>>, stream: OutputStream| {
+                let output = output.clone();
+                let encoding = task.output_encoding;
                 thread::spawn(move || {
-                    let mut reader = BufReader::new(out);
-                    let mut buffer = [0; 1024];
-                    let mut pending = Vec::new();
-                    
-                    loop {
-                        match reader.read(&mut buffer) {
-                            Ok(0) => break, // EOF
-                            Ok(n) => {
-                                pending.extend_from_slice(&buffer[..n]);
-
-                                // Try decoding as UTF-8 first
-                                if let Ok(s) = std::str::from_utf8(&pending) {
-                                    let msg = s.to_string();
-                                    {
-                                        let mut acc = output_clone1.lock().unwrap();
-                                        acc.push_str(&msg);
-                                    }
-                                    let _ = app_handle.emit("task-output", (task_id_t1.clone(), msg));
-                                    pending.clear();
-                                } else {
-                                    // If not valid UTF-8, try decoding as GBK (common Windows encoding in CN)
-                                    // Only do this if we have enough bytes or if the buffer is full?
-                                    // Actually, we should probably just try decoding the whole pending buffer as GBK if UTF-8 fails
-                                    // But what if it's a split UTF-8 character?
-                                    
-                                    // Simple heuristic: Try to decode as GBK using encoding_rs
-                                    // encoding_rs handles partial sequences by returning replacement or valid_up_to logic, 
-                                    // but here we just want to decode what we have if it looks like GBK.
-                                    
-                                    // Note: GBK is a superset of ASCII.
-                                    let (res, _enc, errors) = encoding_rs::GBK.decode(&pending);
-                                    if !errors {
-                                         // It's valid GBK (or ASCII)
-                                         let msg = res.to_string();
-                                         {
-                                             let mut acc = output_clone1.lock().unwrap();
-                                             acc.push_str(&msg);
-                                         }
-                                         let _ = app_handle.emit("task-output", (task_id_t1.clone(), msg));
-                                         pending.clear();
-                                    } else {
-                                         // It's neither valid UTF-8 nor valid GBK (or it's incomplete).
-                                         // Wait for more data? 
-                                         // Since we read 1024 bytes, and typical multi-byte chars are 2-3 bytes, 
-                                         // if we have data left, we can keep it in pending.
-                                         // But if the buffer is full (unlikely to have 1024 bytes of garbage), we might stuck.
-                                         
-                                         // Force decode using GBK lossy if pending gets too large or other conditions?
-                                         // Implementation detail: for now, assume incomplete, wait next read.
-                                         // BUT: if we are at EOF (handled by Ok(0)), we should flush.
-                                         
-                                         // Problem: If we never get valid sequence, we fill memory.
-                                         // Let's rely on standard UTF-8 incomplete handling for now? No, we need GBK.
-                                         
-                                         // Let's decode as GBK lossy immediately for the bulk, handling stream safety?
-                                         // encoding_rs::Decoder is better for streaming.
-                                         
-                                         let (res, _read, replaced) = encoding_rs::GBK.decode(&pending);
-                                         // If replaced is true, it means there were errors. 
-                                         // But maybe it's just incomplete at the end?
-                                         // We really should use a streaming decoder state, but that requires refactoring structure.
-                                         
-                                         // Workaround: Just decode lossy as GBK for display
-                                         let msg = res.to_string();
-                                         {
-                                             let mut acc = output_clone1.lock().unwrap();
-                                             acc.push_str(&msg);
-                                         }
-                                         let _ = app_handle.emit("task-output", (task_id_t1.clone(), msg));
-                                         pending.clear();
-                                    }
-                                }
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                    // Flush remaining if any (shouldn't really happen if stream ends cleanly on char boundary)
-                    if !pending.is_empty() {
-                         let msg = String::from_utf8_lossy(&pending).to_string();
-                         {
-                             let mut acc = output_clone1.lock().unwrap();
-                             acc.push_str(&msg);
-                         }
-                         let _ = app_handle.emit("task-output", (task_id_t1.clone(), msg));
+                    if let Some(pipe) = pipe {
+                        task_output::pump(pipe, encoding, |line| output.push(stream, line));
                     }
                 })
-            } else { thread::spawn(|| {}) };
-
-            let t2 = if let Some(err) = stderr {
-                thread::spawn(move || {
-                    let mut reader = BufReader::new(err);
-                    let mut buffer = [0; 1024];
-                    let mut pending = Vec::new();
-                    
-                    loop {
-                        match reader.read(&mut buffer) {
-                            Ok(0) => break, // EOF
-                            Ok(n) => {
-                                pending.extend_from_slice(&buffer[..n]);
-                                // Try UTF-8 first
-                                if let Ok(s) = std::str::from_utf8(&pending) {
-                                    let msg = s.to_string();
-                                    {
-                                        let mut acc = output_clone2.lock().unwrap();
-                                        acc.push_str(&msg);
-                                    }
-                                    let _ = app_handle_clone.emit("task-output", (task_id_t2.clone(), msg));
-                                    pending.clear();
-                                } else {
-                                    // Fallback to GBK
-                                    let (res, _enc, _errors) = encoding_rs::GBK.decode(&pending);
-                                    let msg = res.to_string(); // This deals with incomplete bytes by using replacements or waiting? 
-                                    // decode() replaces malformed sequences. 
-                                    // For streaming exactness we should usage Decoder, but for simple output logging this is usually acceptable 
-                                    // provided we don't cut in the middle of a multibyte char too often.
-                                    
-                                    {
-                                        let mut acc = output_clone2.lock().unwrap();
-                                        acc.push_str(&msg);
-                                    }
-                                    let _ = app_handle_clone.emit("task-output", (task_id_t2.clone(), msg));
-                                    pending.clear();
-                                }
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                    if !pending.is_empty() {
-                         let msg = String::from_utf8_lossy(&pending).to_string();
-                         {
-                             let mut acc = output_clone2.lock().unwrap();
-                             acc.push_str(&msg);
-                         }
-                         let _ = app_handle_clone.emit("task-output", (task_id_t2.clone(), msg));
-                    }
-                })
-            } else { thread::spawn(|| {}) };
-
-            // Wait for threads (IO) and Child with timeout/polling to allow killing
-            let mut wait_res = Err(std::io::Error::new(std::io::ErrorKind::Other, "Encoding"));
-            let mut loop_count = 0;
-            
-            loop {
-                // Check if child has exited
-                let exited = {
-                    // Scope for locks
-                    let running = scheduler.running_tasks.lock().unwrap();
-                    if let Some(child_arc) = running.get(&task_id) {
-                        let mut child = child_arc.lock().unwrap();
-                         match child.try_wait() {
-                            Ok(Some(status)) => {
-                                wait_res = Ok(status);
-                                true
-                            },
-                            Ok(None) => false, // Still running
-                            Err(e) => {
-                                wait_res = Err(e);
-                                true
-                            }
-                        }
-                    } else {
-                        // Lost child reference?
-                         wait_res = Err(std::io::Error::new(std::io::ErrorKind::Other, "Process lost"));
-                         true
-                    }
-                };
-                
-                if exited {
-                    break;
-                }
-                
-                thread::sleep(Duration::from_millis(200));
-                
-                // Periodically update the task's last_result with partial output
-                // so the UI can see it even if the page is reloaded.
-                loop_count += 1;
-                if loop_count % 5 == 0 { // Every 1 second
-                    let current_output = output_arc.lock().unwrap().clone();
-                     let mut tasks = scheduler.tasks.lock().unwrap();
-                    if let Some(t) = tasks.iter_mut().find(|t| t.id == task_id) {
+            };
+            let pumps = vec![
+                spawn_pump(stdout.map(|out| Box::new(out) as Box<dyn Read + Send>), OutputStream::Stdout),
+                spawn_pump(stderr.map(|err| Box::new(err) as Box<dyn Read + Send>), OutputStream::Stderr),
+            ];
+
+            let exit = wait_for_run(
+                &child,
+                pumps,
+                task.timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs),
+                Duration::from_secs(task.grace_secs),
+                &|line| output.push(OutputStream::Stderr, line),
+                // Keep the task's last_result current so the UI sees the output so far
+                // even if the page is reloaded
+                &mut || {
+                    let current_output = output.text.lock().unwrap().clone();
+                    let mut tasks = scheduler.tasks.lock().unwrap();
+                    if let Some(t) = tasks.iter_mut().find(|t| t.id == task.id) {
                         t.last_result = Some(current_output);
                     }
-                    // Optionally save? Might be too IO intensive.
-                    // scheduler.save(); 
-                }
-            }
+                },
+            );
 
-            // Remove from map
-            {
-                let mut running = scheduler.running_tasks.lock().unwrap();
-                running.remove(&task_id);
-            }
+            // Only now, so the run can be stopped for as long as anything holds its output
+            scheduler.running_tasks.lock().unwrap().remove(&task.id);
             
-            // Join IO threads
-            let _ = t1.join();
-            let _ = t2.join();
-            
-            let mut final_output = output_arc.lock().unwrap().clone();
+            let mut final_output = output.text.lock().unwrap().clone();
             
             // Truncate output if too large to prevent persistent storage bloat
             const MAX_LOG_SIZE: usize = 20 * 1024; // 20KB
             if final_output.len() > MAX_LOG_SIZE {
+                let kept = tail(&final_output, MAX_LOG_SIZE);
                 final_output = format!("... [Output Truncated, {} bytes hidden] ...\n{}", 
-                    final_output.len() - MAX_LOG_SIZE,
-                    &final_output[final_output.len() - MAX_LOG_SIZE..]);
+                    final_output.len() - kept.len(),
+                    kept);
             }
 
-            match wait_res {
+            match exit.status {
+                Ok(status) if exit.timed_out => RunOutcome {
+                    last_result: format!("Timed out after {}s:\n{}", task.timeout_secs.unwrap_or_default(), final_output),
+                    status: RunStatus::TimedOut,
+                    exit_code: status.code(),
+                },
                 Ok(status) => {
+                    let exit_code = status.code();
                     if status.success() {
-                        if final_output.trim().is_empty() { "Success".to_string() } else { final_output }
+                        RunOutcome {
+                            last_result: if final_output.trim().is_empty() { "Success".to_string() } else { final_output },
+                            status: RunStatus::Success,
+                            exit_code,
+                        }
                     } else {
-                        format!("Error (Exit Code {}):\n{}", status.code().unwrap_or(-1), final_output)
+                        RunOutcome {
+                            last_result: format!("Error (Exit Code {}):\n{}", exit_code.unwrap_or(-1), final_output),
+                            status: RunStatus::Failed,
+                            exit_code,
+                        }
                     }
                 },
-                Err(e) => format!("Process Error: {}\nOutput:\n{}", e, final_output)
+                Err(e) => RunOutcome {
+                    last_result: format!("Process Error: {}\nOutput:\n{}", e, final_output),
+                    status: RunStatus::Error,
+                    exit_code: None,
+                },
             }
         },
-        Err(e) => format!("Execution Failed: {}", e),
+        Err(e) => {
+            let last_result = format!("Execution Failed: {}", e);
+            output.push(OutputStream::Stderr, &last_result);
+            RunOutcome {
+                last_result,
+                status: RunStatus::Error,
+                exit_code: None,
+            }
+        }
     }
 }
 
+/// Refuses limits this platform would silently ignore.
+fn validate(task: &Task) -> Result<(), String> {
+    if !task.limits.is_empty() && !process_limits::COMMAND_LIMITS_SUPPORTED {
+        return Err("Resource limits are not supported on this platform".to_string());
+    }
+    Ok(())
+}
+
 // Commands
 #[tauri::command]
 pub async fn get_tasks(state: State<'_, SchedulerState>) -> Result<Vec<Task>, ()> {
@@ -457,23 +535,27 @@ pub async fn get_tasks(state: State<'_, SchedulerState>) -> Result<Vec<Task>, ()
 }
 
 #[tauri::command]
-pub async fn add_task(mut task: Task, state: State<'_, SchedulerState>) -> Result<(), ()> {
+pub async fn add_task(mut task: Task, state: State<'_, SchedulerState>) -> Result<(), String> {
+    validate(&task)?;
     task.id = uuid::Uuid::new_v4().to_string();
     let mut tasks = state.0.tasks.lock().unwrap();
     tasks.push(task);
     drop(tasks);
     state.0.save();
+    state.0.notify();
     Ok(())
 }
 
 #[tauri::command]
-pub async fn update_task(task: Task, state: State<'_, SchedulerState>) -> Result<(), ()> {
+pub async fn update_task(task: Task, state: State<'_, SchedulerState>) -> Result<(), String> {
+    validate(&task)?;
     let mut tasks = state.0.tasks.lock().unwrap();
     if let Some(t) = tasks.iter_mut().find(|t| t.id == task.id) {
         *t = task;
     }
     drop(tasks);
     state.0.save();
+    state.0.notify();
     Ok(())
 }
 
@@ -483,6 +565,8 @@ pub async fn delete_task(id: String, state: State<'_, SchedulerState>) -> Result
     tasks.retain(|t| t.id != id);
     drop(tasks);
     state.0.save();
+    state.0.history.remove_task(&id);
+    state.0.notify();
     Ok(())
 }
 
@@ -490,41 +574,165 @@ pub async fn delete_task(id: String, state: State<'_, SchedulerState>) -> Result
 pub async fn run_task_manual(id: String, state: State<'_, SchedulerState>) -> Result<(), ()> {
     let state_clone = (*state).clone();
     thread::spawn(move || {
-        run_task_internal(&state_clone, &id);
+        run_task_internal(&state_clone, &id, RunTrigger::Manual);
     });
     Ok(())
 }
 
 #[tauri::command]
 pub async fn stop_task(id: String, state: State<'_, SchedulerState>) -> Result<(), ()> {
-    let scheduler = &state.0;
-    
-    // Get PID and Child reference safely
-    let (pid, child_arc) = {
-        let running = scheduler.running_tasks.lock().unwrap();
-        if let Some(child_arc) = running.get(&id) {
-            let child = child_arc.lock().unwrap();
-            (child.id(), child_arc.clone())
-        } else {
-            return Ok(());
+    let running_tasks = state.0.running_tasks.clone();
+    // Scans the process table, so keep it off the async runtime
+    let _ = tauri::async_runtime::spawn_blocking(move || stop_running(&running_tasks, &id)).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::io::{BufRead, BufReader};
+
+    #[test]
+    fn tail_cuts_on_a_character_boundary() {
+        // Each "é" is two bytes, so a cut at an odd offset falls inside one
+        let text = "abcéé";
+        assert_eq!(tail(text, 4), "éé");
+        assert_eq!(tail(text, 3), "é");
+        assert_eq!(tail(text, 1), "");
+        assert_eq!(tail(text, 100), text);
+        assert_eq!(tail("日本語", 4), "語");
+    }
+
+    /// Zombies waiting to be reaped by init count as gone.
+    #[cfg(unix)]
+    fn is_running(pid: u32) -> bool {
+        let output = Command::new("ps")
+            .args(["-o", "stat=", "-p", &pid.to_string()])
+            .output()
+            .expect("failed to run ps");
+        let stat = String::from_utf8_lossy(&output.stdout);
+        output.status.success() && !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+    }
+
+    #[cfg(unix)]
+    fn wait_until_gone(pid: u32) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while is_running(pid) {
+            if Instant::now() > deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(50));
         }
-    };
+        true
+    }
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        // Force kill process tree
-        let _ = Command::new("taskkill")
-            .args(&["/F", "/T", "/PID", &pid.to_string()])
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn();
+    /// Starts `script` as the running task "task" and returns it with the PID it prints.
+    #[cfg(unix)]
+    fn start(script: &str) -> (RunningTasks, Arc<Mutex<Child>>, u32) {
+        let mut command = shell_command(script, &[]);
+        command.stdout(Stdio::piped());
+        let mut child = command.spawn().expect("failed to spawn task");
+
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let pid: u32 = line.trim().parse().expect("task did not print a PID");
+        assert!(is_running(pid), "{} is not running before stop", pid);
+
+        let child = Arc::new(Mutex::new(child));
+        let running = Mutex::new(HashMap::from([("task".to_string(), child.clone())]));
+        (running, child, pid)
     }
 
-    {
-        let mut child = child_arc.lock().unwrap();
-        let _ = child.kill();
+    #[cfg(unix)]
+    fn stop_and_check(script: &str) {
+        let (running, child, pid) = start(script);
+        stop_running(&running, "task");
+        let _ = child.lock().unwrap().wait();
+        assert!(wait_until_gone(pid), "{} survived stop", pid);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_kills_sleep_in_nested_shell() {
+        stop_and_check("sh -c 'sleep 300 & echo $!; wait'; sleep 300");
+    }
+
+    /// `setsid` moves the sleep out of the task's process group; it is found through its parent.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn stop_kills_sleep_that_left_the_group() {
+        stop_and_check("setsid sleep 300 & echo $!; wait");
+    }
+
+    /// After the shell is reaped only its process group is killed, which still reaches
+    /// what it left running in the background.
+    #[cfg(unix)]
+    #[test]
+    fn stop_after_exit_kills_the_group() {
+        let (running, child, pid) = start("sleep 300 & echo $!");
+        while child.lock().unwrap().try_wait().unwrap().is_none() {
+            thread::sleep(Duration::from_millis(20));
+        }
+        assert!(is_running(pid), "{} exited with the shell", pid);
+
+        stop_running(&running, "task");
+        assert!(wait_until_gone(pid), "{} survived stop", pid);
+    }
+
+    /// Runs `script` through `wait_for_run` as the running task "task", stopping it after
+    /// `stop_after` if given. Returns how the run ended, its stdout and how long it took.
+    #[cfg(unix)]
+    fn run(script: &str, timeout: Option<Duration>, stop_after: Option<Duration>) -> (RunExit, String, Duration) {
+        let mut command = shell_command(script, &[]);
+        command.stdout(Stdio::piped());
+        let mut child = command.spawn().expect("failed to spawn task");
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let text = Arc::new(Mutex::new(String::new()));
+        let pump = {
+            let text = text.clone();
+            thread::spawn(move || {
+                for line in stdout.lines().map_while(Result::ok) {
+                    text.lock().unwrap().push_str(&line);
+                }
+            })
+        };
+
+        let child = Arc::new(Mutex::new(child));
+        let running: Arc<RunningTasks> = Arc::new(Mutex::new(HashMap::from([("task".to_string(), child.clone())])));
+        if let Some(delay) = stop_after {
+            let running = running.clone();
+            thread::spawn(move || {
+                thread::sleep(delay);
+                stop_running(&running, "task");
+            });
+        }
+
+        let started = Instant::now();
+        let exit = wait_for_run(&child, vec![pump], timeout, Duration::from_secs(1), &|_| {}, &mut || {});
+        let stdout = text.lock().unwrap().clone();
+        (exit, stdout, started.elapsed())
+    }
+
+    /// The shell exits at once, but the sleep keeps its stdout open.
+    #[cfg(unix)]
+    #[test]
+    fn stop_ends_a_run_held_open_by_a_background_child() {
+        let (exit, stdout, took) = run("sleep 30 & echo $!", None, Some(Duration::from_millis(500)));
+        assert!(took < Duration::from_secs(5), "stopped run took {:?}", took);
+        assert!(!exit.timed_out);
+        assert!(exit.status.unwrap().success());
+        let pid: u32 = stdout.trim().parse().expect("task did not print a PID");
+        assert!(wait_until_gone(pid), "{} survived stop", pid);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_ends_a_run_held_open_by_a_background_child() {
+        let (exit, stdout, took) = run("sleep 30 & echo $!", Some(Duration::from_secs(1)), None);
+        assert!(took < Duration::from_secs(5), "timed-out run took {:?}", took);
+        assert!(exit.timed_out);
+        let pid: u32 = stdout.trim().parse().expect("task did not print a PID");
+        assert!(wait_until_gone(pid), "{} survived the timeout", pid);
     }
-    
-    Ok(())
 }
@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use tauri::State;
+
+use crate::monitor::MonitorState;
+use crate::{ports, ProcessInfo};
+
+const CSV_COLUMNS: [&str; 10] = ["pid", "ppid", "name", "user", "status", "cpu_usage", "memory", "ports", "exe", "cmd"];
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// What `export_processes` writes in JSON format.
+#[derive(Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    /// RFC 3339 local time.
+    pub captured_at: String,
+    pub processes: Vec<ProcessInfo>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct UsageChange {
+    pub pid: u32,
+    pub name: String,
+    pub memory_before: u64,
+    pub memory_after: u64,
+    pub cpu_before: f32,
+    pub cpu_after: f32,
+}
+
+#[derive(Serialize, Clone)]
+pub struct SnapshotDiff {
+    pub started: Vec<ProcessInfo>,
+    pub ended: Vec<ProcessInfo>,
+    pub changed: Vec<UsageChange>,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(processes: &[ProcessInfo]) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+    for p in processes {
+        let ports: Vec<String> = p.ports.iter().map(|port| port.to_string()).collect();
+        let row = [
+            p.pid.to_string(),
+            p.ppid.map(|ppid| ppid.to_string()).unwrap_or_default(),
+            p.name.clone(),
+            p.user.clone().unwrap_or_default(),
+            p.status.clone(),
+            p.cpu_usage.to_string(),
+            p.memory.to_string(),
+            ports.join(";"),
+            p.exe.clone(),
+            p.cmd.clone(),
+        ];
+        let fields: Vec<String> = row.iter().map(|v| csv_field(v)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Splits CSV text into rows of fields, honouring quotes and embedded newlines.
+fn parse_csv_rows(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn from_csv(content: &str) -> Result<Vec<ProcessInfo>, String> {
+    let mut rows = parse_csv_rows(content).into_iter();
+    let header = rows.next().ok_or("Snapshot is empty")?;
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let pid_col = column("pid").ok_or("Snapshot has no pid column")?;
+    let columns: HashMap<&str, usize> = CSV_COLUMNS.iter().filter_map(|c| Some((*c, column(c)?))).collect();
+
+    let mut processes = Vec::new();
+    for (line, row) in rows.enumerate() {
+        if row.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        let get = |name: &str| columns.get(name).and_then(|i| row.get(*i)).map(String::as_str).unwrap_or_default();
+        let pid = row
+            .get(pid_col)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("Invalid pid on row {}", line + 2))?;
+        processes.push(ProcessInfo {
+            pid,
+            ppid: get("ppid").parse().ok(),
+            name: get("name").to_string(),
+            exe: get("exe").to_string(),
+            cmd: get("cmd").to_string(),
+            memory: get("memory").parse().unwrap_or_default(),
+            cpu_usage: get("cpu_usage").parse().unwrap_or_default(),
+            status: get("status").to_string(),
+            user: Some(get("user").to_string()).filter(|u| !u.is_empty()),
+            ports: get("ports").split(';').filter_map(|p| p.parse().ok()).collect(),
+            sockets: Vec::new(),
+            window_handle: None,
+        });
+    }
+    Ok(processes)
+}
+
+/// Reads a snapshot written by `export_processes`, detecting JSON vs CSV from the content.
+fn load_snapshot(path: &str) -> Result<Vec<ProcessInfo>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    match content.trim_start().chars().next() {
+        Some('{') => serde_json::from_str::<ProcessSnapshot>(&content)
+            .map(|s| s.processes)
+            .map_err(|e| format!("Invalid snapshot {}: {}", path, e)),
+        Some('[') => serde_json::from_str(&content).map_err(|e| format!("Invalid snapshot {}: {}", path, e)),
+        _ => from_csv(&content).map_err(|e| format!("Invalid snapshot {}: {}", path, e)),
+    }
+}
+
+fn diff(before: Vec<ProcessInfo>, after: Vec<ProcessInfo>) -> SnapshotDiff {
+    let mut before: HashMap<u32, ProcessInfo> = before.into_iter().map(|p| (p.pid, p)).collect();
+    let mut started = Vec::new();
+    let mut changed = Vec::new();
+
+    for p in after {
+        match before.remove(&p.pid) {
+            // Same PID with a different name means the PID was reused
+            Some(old) if old.name == p.name => {
+                if old.memory != p.memory || (old.cpu_usage - p.cpu_usage).abs() >= 0.1 {
+                    changed.push(UsageChange {
+                        pid: p.pid,
+                        name: p.name,
+                        memory_before: old.memory,
+                        memory_after: p.memory,
+                        cpu_before: old.cpu_usage,
+                        cpu_after: p.cpu_usage,
+                    });
+                }
+            }
+            Some(old) => {
+                before.insert(old.pid, old);
+                started.push(p);
+            }
+            None => started.push(p),
+        }
+    }
+
+    let mut ended: Vec<ProcessInfo> = before.into_values().collect();
+    started.sort_by_key(|p| p.pid);
+    ended.sort_by_key(|p| p.pid);
+    changed.sort_by_key(|c| c.pid);
+    SnapshotDiff { started, ended, changed }
+}
+
+/// Writes the current process list (with ports and sockets) to `path`. Returns how
+/// many processes were written.
+#[tauri::command]
+pub fn export_processes(format: ExportFormat, path: String, monitor: State<'_, MonitorState>) -> Result<usize, String> {
+    let sockets_map = ports::get_netstat_sockets();
+    let mut processes = monitor.0.processes();
+    crate::attach_sockets(&mut processes, &sockets_map);
+    processes.sort_by_key(|p| p.pid);
+    let count = processes.len();
+
+    let content = match format {
+        ExportFormat::Json => {
+            let snapshot = ProcessSnapshot {
+                captured_at: chrono::Local::now().to_rfc3339(),
+                processes,
+            };
+            serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?
+        }
+        ExportFormat::Csv => to_csv(&processes),
+    };
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(count)
+}
+
+/// Processes that started, ended or changed CPU/memory between snapshots `a` and `b`.
+#[tauri::command]
+pub fn diff_process_snapshots(a: String, b: String) -> Result<SnapshotDiff, String> {
+    Ok(diff(load_snapshot(&a)?, load_snapshot(&b)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, memory: u64, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: None,
+            name: name.to_string(),
+            exe: String::new(),
+            cmd: String::new(),
+            memory,
+            cpu_usage,
+            status: "Run".to_string(),
+            user: None,
+            ports: Vec::new(),
+            sockets: Vec::new(),
+            window_handle: None,
+        }
+    }
+
+    fn pids(processes: &[ProcessInfo]) -> Vec<u32> {
+        processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn csv_round_trips_awkward_fields() {
+        let mut awkward = process(42, "my, \"app\"", 1024, 12.5);
+        awkward.ppid = Some(1);
+        awkward.user = Some("root".to_string());
+        awkward.ports = vec![80, 443];
+        awkward.exe = "C:\\Program Files\\app.exe".to_string();
+        awkward.cmd = "app --title \"a, b\"\n--flag=\"\"\r\nlast".to_string();
+        let plain = process(7, "init", 0, 0.0);
+
+        let csv = to_csv(&[awkward.clone(), plain]);
+        let parsed = from_csv(&csv).unwrap();
+        assert_eq!(pids(&parsed), [42, 7]);
+        let p = &parsed[0];
+        assert_eq!((p.ppid, p.name.as_str(), p.user.as_deref()), (Some(1), awkward.name.as_str(), Some("root")));
+        assert_eq!((p.memory, p.cpu_usage, p.ports.as_slice()), (1024, 12.5, &[80, 443][..]));
+        assert_eq!(p.exe, awkward.exe);
+        assert_eq!(p.cmd, awkward.cmd);
+        assert_eq!((parsed[1].ppid, parsed[1].user.as_deref(), parsed[1].ports.len()), (None, None, 0));
+    }
+
+    #[test]
+    fn diff_reports_started_ended_and_changed() {
+        let before = vec![
+            process(1, "init", 100, 0.0),
+            process(2, "gone", 100, 0.0),
+            process(3, "grew", 100, 1.0),
+            process(4, "busy", 100, 1.0),
+            process(5, "reused", 100, 0.0),
+        ];
+        let after = vec![
+            process(1, "init", 100, 0.05),
+            process(3, "grew", 200, 1.0),
+            process(4, "busy", 100, 50.0),
+            process(5, "other", 100, 0.0),
+            process(6, "new", 100, 0.0),
+        ];
+
+        let diff = diff(before, after);
+        // PID 5 changing its name counts as one process ending and another starting
+        assert_eq!(pids(&diff.started), [5, 6]);
+        assert_eq!(pids(&diff.ended), [2, 5]);
+        assert_eq!(diff.ended[1].name, "reused");
+        let changed: Vec<(u32, u64, u64, f32, f32)> = diff
+            .changed
+            .iter()
+            .map(|c| (c.pid, c.memory_before, c.memory_after, c.cpu_before, c.cpu_after))
+            .collect();
+        assert_eq!(changed, [(3, 100, 200, 1.0, 1.0), (4, 100, 100, 1.0, 50.0)]);
+    }
+
+    #[test]
+    fn diff_process_snapshots_reads_json_and_csv() {
+        let dir = std::env::temp_dir().join(format!("atools-snapshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.json");
+        let b = dir.join("b.csv");
+        let snapshot = ProcessSnapshot {
+            captured_at: String::new(),
+            processes: vec![process(1, "init", 100, 0.0), process(2, "gone", 100, 0.0)],
+        };
+        fs::write(&a, serde_json::to_string(&snapshot).unwrap()).unwrap();
+        fs::write(&b, to_csv(&[process(1, "init", 300, 0.0), process(3, "new", 100, 0.0)])).unwrap();
+
+        let diff = diff_process_snapshots(a.display().to_string(), b.display().to_string());
+        let missing = diff_process_snapshots(dir.join("missing").display().to_string(), b.display().to_string());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let diff = diff.unwrap();
+        assert_eq!((pids(&diff.started), pids(&diff.ended)), (vec![3], vec![2]));
+        assert_eq!(diff.changed.iter().map(|c| (c.pid, c.memory_after)).collect::<Vec<_>>(), [(1, 300)]);
+        assert!(matches!(missing, Err(e) if e.starts_with("Failed to read")));
+    }
+}
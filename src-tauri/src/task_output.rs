@@ -0,0 +1,377 @@
+use encoding_rs::{Decoder, Encoding, GBK};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::str::FromStr;
+
+/// A line longer than this is passed on without waiting for its newline, so progress
+/// output that never ends a line still shows up.
+const MAX_LINE_LEN: usize = 8 * 1024;
+
+/// Encoding used when `Auto` finds output that isn't UTF-8 (native Windows commands
+/// on Chinese systems write the ANSI code page).
+const AUTO_FALLBACK: &Encoding = GBK;
+
+/// How a task's stdout/stderr bytes are turned into text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum OutputEncoding {
+    /// UTF-8 unless the output starts with a BOM or turns out not to be UTF-8, in which
+    /// case the rest is decoded as GBK.
+    #[default]
+    Auto,
+    /// Any WHATWG encoding label (`utf-8`, `gbk`, `shift_jis`, `utf-16le`, ...) except the
+    /// ones for the replacement encoding.
+    Fixed(&'static Encoding),
+}
+
+impl FromStr for OutputEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let label = s.trim();
+        if label.is_empty() || label.eq_ignore_ascii_case("auto") {
+            return Ok(OutputEncoding::Auto);
+        }
+        // Labels like "iso-2022-kr" map to the replacement encoding, whose name is no label
+        // at all, so it could be saved but never read back
+        Encoding::for_label_no_replacement(label.as_bytes())
+            .map(OutputEncoding::Fixed)
+            .ok_or_else(|| format!("Unknown output encoding: {}", label))
+    }
+}
+
+impl TryFrom<String> for OutputEncoding {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for OutputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputEncoding::Auto => f.write_str("auto"),
+            OutputEncoding::Fixed(encoding) => f.write_str(&encoding.name().to_ascii_lowercase()),
+        }
+    }
+}
+
+impl From<OutputEncoding> for String {
+    fn from(encoding: OutputEncoding) -> Self {
+        encoding.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of task output: the `task-output` event payload and one line of a run log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOutput {
+    pub run_id: String,
+    pub task_id: String,
+    pub stream: OutputStream,
+    /// Unix time in milliseconds.
+    pub ts: i64,
+    /// The line without its line ending.
+    pub text: String,
+}
+
+enum DecoderState {
+    /// `Auto` while everything so far has been UTF-8. Holds an incomplete sequence (or
+    /// a partial BOM) left over from the previous chunk.
+    Sniffing { pending: Vec<u8>, started: bool },
+    Decoding(Decoder),
+}
+
+/// Turns a byte stream into text chunk by chunk. Multibyte sequences split across
+/// chunks are carried over rather than replaced.
+pub struct OutputDecoder {
+    state: DecoderState,
+}
+
+const BOMS: [&[u8]; 3] = [b"\xEF\xBB\xBF", b"\xFF\xFE", b"\xFE\xFF"];
+
+fn decode_with(decoder: &mut Decoder, bytes: &[u8], last: bool) -> String {
+    let capacity = decoder.max_utf8_buffer_length(bytes.len()).unwrap_or(bytes.len() * 3 + 16);
+    let mut out = String::with_capacity(capacity);
+    // The buffer is large enough for all of `bytes`, so this never stops early
+    let _ = decoder.decode_to_string(bytes, &mut out, last);
+    out
+}
+
+impl OutputDecoder {
+    pub fn new(encoding: OutputEncoding) -> Self {
+        let state = match encoding {
+            OutputEncoding::Auto => DecoderState::Sniffing {
+                pending: Vec::new(),
+                started: false,
+            },
+            // new_decoder() still honours a BOM if the output has one
+            OutputEncoding::Fixed(encoding) => DecoderState::Decoding(encoding.new_decoder()),
+        };
+        OutputDecoder { state }
+    }
+
+    /// Decodes the next chunk. May return less text than `bytes` holds when it ends
+    /// mid-character; the rest comes out with the next chunk or `finish`.
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        let (pending, started) = match &mut self.state {
+            DecoderState::Decoding(decoder) => return decode_with(decoder, bytes, false),
+            DecoderState::Sniffing { pending, started } => (pending, started),
+        };
+        pending.extend_from_slice(bytes);
+
+        if !*started {
+            if pending.len() < 3 && BOMS.iter().any(|bom| bom.starts_with(pending)) {
+                return String::new();
+            }
+            *started = true;
+            if let Some((encoding, _)) = Encoding::for_bom(pending) {
+                let pending = std::mem::take(pending);
+                return self.switch_to(encoding.new_decoder(), String::new(), &pending);
+            }
+        }
+
+        match std::str::from_utf8(pending) {
+            Ok(text) => {
+                let text = text.to_string();
+                pending.clear();
+                text
+            }
+            // Only an incomplete sequence at the end: keep it for the next chunk
+            Err(e) if e.error_len().is_none() => {
+                let valid = e.valid_up_to();
+                let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+                pending.drain(..valid);
+                text
+            }
+            Err(e) => {
+                let pending = std::mem::take(pending);
+                let valid = e.valid_up_to();
+                let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+                self.switch_to(AUTO_FALLBACK.new_decoder_without_bom_handling(), text, &pending[valid..])
+            }
+        }
+    }
+
+    /// Leaves `Auto` sniffing for `decoder`, which decodes `rest` onto `text`.
+    fn switch_to(&mut self, mut decoder: Decoder, mut text: String, rest: &[u8]) -> String {
+        text.push_str(&decode_with(&mut decoder, rest, false));
+        self.state = DecoderState::Decoding(decoder);
+        text
+    }
+
+    /// Flushes whatever is left at end of stream; a truncated sequence becomes U+FFFD.
+    pub fn finish(&mut self) -> String {
+        match &mut self.state {
+            DecoderState::Sniffing { pending, .. } => {
+                let text = String::from_utf8_lossy(pending).into_owned();
+                pending.clear();
+                text
+            }
+            DecoderState::Decoding(decoder) => decode_with(decoder, &[], true),
+        }
+    }
+}
+
+/// Cuts decoded text into lines, holding back an unfinished last line.
+#[derive(Default)]
+pub struct LineSplitter {
+    partial: String,
+}
+
+impl LineSplitter {
+    pub fn push(&mut self, text: &str, mut on_line: impl FnMut(&str)) {
+        self.partial.push_str(text);
+        let mut start = 0;
+        while let Some(len) = self.partial[start..].find('\n') {
+            let line = &self.partial[start..start + len];
+            on_line(line.strip_suffix('\r').unwrap_or(line));
+            start += len + 1;
+        }
+        self.partial.drain(..start);
+        if self.partial.len() >= MAX_LINE_LEN {
+            on_line(&std::mem::take(&mut self.partial));
+        }
+    }
+
+    /// Passes on the last line if the output didn't end with a newline.
+    pub fn finish(&mut self, mut on_line: impl FnMut(&str)) {
+        if !self.partial.is_empty() {
+            on_line(&std::mem::take(&mut self.partial));
+        }
+    }
+}
+
+/// Reads `reader` to the end, handing each decoded line to `on_line`.
+/// Shared by the stdout and stderr threads of a task run.
+pub fn pump(mut reader: impl Read, encoding: OutputEncoding, mut on_line: impl FnMut(&str)) {
+    let mut decoder = OutputDecoder::new(encoding);
+    let mut lines = LineSplitter::default();
+    let mut buffer = [0; 4096];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => lines.push(&decoder.decode(&buffer[..n]), &mut on_line),
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    lines.push(&decoder.finish(), &mut on_line);
+    lines.finish(on_line);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, UTF_8};
+
+    const TEXT: &str = "héllo 世界 🎉 done";
+
+    /// Feeds `bytes` one at a time, the worst case for split sequences.
+    fn decode_bytewise(encoding: OutputEncoding, bytes: &[u8]) -> String {
+        let mut decoder = OutputDecoder::new(encoding);
+        let mut out: String = bytes.iter().map(|b| decoder.decode(&[*b])).collect();
+        out.push_str(&decoder.finish());
+        out
+    }
+
+    fn fixed(label: &str) -> OutputEncoding {
+        label.parse().unwrap()
+    }
+
+    /// A reader that returns at most `step` bytes per read.
+    struct Trickle<'a> {
+        bytes: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.step.min(self.bytes.len()).min(buf.len());
+            buf[..n].copy_from_slice(&self.bytes[..n]);
+            self.bytes = &self.bytes[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn encodings_round_trip_through_serde() {
+        for label in ["auto", "utf-8", "GBK", "shift_jis", "utf-16le", "x-user-defined", "latin1"] {
+            let encoding = fixed(label);
+            let json = serde_json::to_string(&encoding).unwrap();
+            assert_eq!(serde_json::from_str::<OutputEncoding>(&json).unwrap(), encoding, "{}", label);
+        }
+        assert_eq!(serde_json::to_string(&fixed("latin1")).unwrap(), "\"windows-1252\"");
+
+        for label in ["iso-2022-kr", "hz-gb-2312", "replacement", "utf-9"] {
+            assert!(label.parse::<OutputEncoding>().is_err(), "{}", label);
+            assert!(serde_json::from_str::<OutputEncoding>(&format!("\"{}\"", label)).is_err(), "{}", label);
+        }
+    }
+
+    #[test]
+    fn utf8_split_bytewise() {
+        assert_eq!(decode_bytewise(fixed("utf-8"), TEXT.as_bytes()), TEXT);
+        assert_eq!(decode_bytewise(OutputEncoding::Auto, TEXT.as_bytes()), TEXT);
+    }
+
+    #[test]
+    fn gbk_split_bytewise() {
+        let (bytes, _, _) = GBK.encode("中文输出: 成功");
+        assert_eq!(decode_bytewise(fixed("gbk"), &bytes), "中文输出: 成功");
+    }
+
+    #[test]
+    fn shift_jis_split_bytewise() {
+        let (bytes, _, _) = SHIFT_JIS.encode("日本語のテキスト");
+        assert_eq!(decode_bytewise(fixed("shift_jis"), &bytes), "日本語のテキスト");
+    }
+
+    #[test]
+    fn utf16le_split_bytewise() {
+        // Includes a surrogate pair, split across four reads
+        let bytes: Vec<u8> = TEXT.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+        assert_eq!(decode_bytewise(fixed("utf-16le"), &bytes), TEXT);
+    }
+
+    #[test]
+    fn auto_falls_back_to_gbk() {
+        let (bytes, _, _) = GBK.encode("ok 中文");
+        assert_eq!(decode_bytewise(OutputEncoding::Auto, &bytes), "ok 中文");
+    }
+
+    #[test]
+    fn auto_follows_utf16_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(TEXT.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        assert_eq!(decode_bytewise(OutputEncoding::Auto, &bytes), TEXT);
+    }
+
+    #[test]
+    fn auto_strips_utf8_bom() {
+        let mut bytes = b"\xEF\xBB\xBF".to_vec();
+        bytes.extend_from_slice(TEXT.as_bytes());
+        assert_eq!(decode_bytewise(OutputEncoding::Auto, &bytes), TEXT);
+    }
+
+    #[test]
+    fn truncated_sequence_is_replaced_at_end() {
+        let bytes = &"世".as_bytes()[..2];
+        assert_eq!(decode_bytewise(OutputEncoding::Auto, bytes), "\u{FFFD}");
+        assert_eq!(decode_bytewise(fixed("utf-8"), bytes), "\u{FFFD}");
+    }
+
+    #[test]
+    fn pump_joins_small_reads() {
+        let bytes: Vec<u8> = TEXT.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect();
+
+        for step in 1..=5 {
+            let mut lines = Vec::new();
+            pump(Trickle { bytes: &bytes, step }, fixed("utf-16le"), |line| lines.push(line.to_string()));
+            assert_eq!(lines, vec![TEXT], "step {}", step);
+        }
+    }
+
+    #[test]
+    fn pump_splits_lines() {
+        let (bytes, _, _) = GBK.encode("第一行\r\n第二行\n\n最后");
+        let mut lines = Vec::new();
+        pump(Trickle { bytes: &bytes, step: 1 }, OutputEncoding::Auto, |line| lines.push(line.to_string()));
+        assert_eq!(lines, vec!["第一行", "第二行", "", "最后"]);
+    }
+
+    #[test]
+    fn long_line_is_not_held_back() {
+        let mut splitter = LineSplitter::default();
+        let mut lines = Vec::new();
+        splitter.push(&"#".repeat(MAX_LINE_LEN - 1), |line| lines.push(line.len()));
+        assert!(lines.is_empty());
+        splitter.push("##\nend", |line| lines.push(line.len()));
+        assert_eq!(lines, vec![MAX_LINE_LEN + 1]);
+        splitter.finish(|line| lines.push(line.len()));
+        assert_eq!(lines, vec![MAX_LINE_LEN + 1, 3]);
+    }
+
+    #[test]
+    fn parses_labels() {
+        assert_eq!(fixed("auto"), OutputEncoding::Auto);
+        assert_eq!(fixed(""), OutputEncoding::Auto);
+        assert_eq!(fixed("UTF8"), OutputEncoding::Fixed(UTF_8));
+        assert_eq!(fixed("gb2312"), OutputEncoding::Fixed(GBK));
+        assert_eq!(fixed("Shift_JIS").to_string(), "shift_jis");
+        assert!("klingon".parse::<OutputEncoding>().is_err());
+
+        let json = serde_json::to_string(&fixed("utf-16le")).unwrap();
+        assert_eq!(json, "\"utf-16le\"");
+        assert_eq!(serde_json::from_str::<OutputEncoding>(&json).unwrap(), fixed("utf-16le"));
+        assert!(serde_json::from_str::<OutputEncoding>("\"nope\"").is_err());
+    }
+}
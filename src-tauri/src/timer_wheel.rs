@@ -0,0 +1,505 @@
+use chrono::{DateTime, Local, TimeDelta};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::iter;
+use std::str::FromStr;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Longest single sleep. Timeouts run on a monotonic clock, so this bounds how late
+/// a run can be after the wall clock jumps (suspend, NTP, manual changes).
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// An occurrence handled more than this late counts as missed and goes through the
+/// task's `MisfirePolicy`.
+const MISFIRE_GRACE: TimeDelta = TimeDelta::seconds(60);
+
+/// Why `Clock::sleep_until` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wake {
+    /// The deadline (or the maximum sleep) passed.
+    Timeout,
+    /// Tasks were added, updated or deleted.
+    Changed,
+    /// Every sender is gone; the scheduler is shutting down.
+    Closed,
+}
+
+/// What to do with occurrences missed while the machine was asleep or aTools was closed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Drop missed runs and wait for the next occurrence.
+    #[default]
+    Skip,
+    /// Run once, however many occurrences were missed.
+    RunOnce,
+    /// Run once per missed occurrence, at most `max_runs` times.
+    RunAll { max_runs: u32 },
+}
+
+/// Why a scheduled run was started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    /// An occurrence that came up on time.
+    Cron,
+    /// Making up for a missed occurrence.
+    CatchUp,
+}
+
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+    /// Blocks until `deadline` (forever if `None`) or until a message arrives on `wake`.
+    fn sleep_until(&self, wake: &Receiver<()>, deadline: Option<DateTime<Local>>) -> Wake;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
+    fn sleep_until(&self, wake: &Receiver<()>, deadline: Option<DateTime<Local>>) -> Wake {
+        let timeout = deadline
+            .map(|d| (d - Local::now()).to_std().unwrap_or(Duration::ZERO))
+            .unwrap_or(MAX_SLEEP)
+            .min(MAX_SLEEP);
+        match wake.recv_timeout(timeout) {
+            Ok(()) => {
+                // Coalesce a burst of edits into one reload
+                while wake.try_recv().is_ok() {}
+                Wake::Changed
+            }
+            Err(RecvTimeoutError::Timeout) => Wake::Timeout,
+            Err(RecvTimeoutError::Disconnected) => Wake::Closed,
+        }
+    }
+}
+
+/// A scheduled task as the wheel sees it.
+#[derive(Debug, Clone)]
+pub struct ScheduleSpec {
+    pub id: String,
+    pub cron_expr: String,
+    pub misfire: MisfirePolicy,
+    /// When the task last ran; occurrences after it are caught up at startup.
+    pub last_run: Option<DateTime<Local>>,
+}
+
+struct Entry {
+    cron_expr: String,
+    schedule: Schedule,
+    misfire: MisfirePolicy,
+    next: Option<DateTime<Local>>,
+}
+
+impl Entry {
+    /// The runs owed for the occurrences from `first` up to `now`.
+    fn due_runs(&self, first: DateTime<Local>, now: DateTime<Local>) -> Vec<Trigger> {
+        let cutoff = now - MISFIRE_GRACE;
+        let on_time = first >= cutoff || self.schedule.after(&cutoff).next().is_some_and(|t| t <= now);
+        let cap = match self.misfire {
+            MisfirePolicy::Skip => 0,
+            MisfirePolicy::RunOnce => 1,
+            MisfirePolicy::RunAll { max_runs } => max_runs as usize,
+        };
+        // Bounded by the cap, so an every-second task after a week asleep stays cheap
+        let missed = iter::once(first)
+            .chain(self.schedule.after(&first))
+            .take_while(|t| *t < cutoff)
+            .take(cap)
+            .count();
+
+        let mut runs = vec![Trigger::CatchUp; missed];
+        if on_time {
+            if self.misfire == MisfirePolicy::RunOnce {
+                runs.clear();
+            }
+            runs.push(Trigger::Cron);
+        }
+        runs
+    }
+}
+
+/// Parsed schedules and the next fire time of every enabled task.
+#[derive(Default)]
+pub struct TimerWheel {
+    entries: HashMap<String, Entry>,
+    last_now: Option<DateTime<Local>>,
+}
+
+impl TimerWheel {
+    /// Syncs with the current task list. Only new or edited expressions are parsed;
+    /// unparseable ones are left out. With `catch_up`, new entries start from their
+    /// `last_run` so occurrences missed while aTools was closed come due right away.
+    pub fn reload(&mut self, schedules: Vec<ScheduleSpec>, now: DateTime<Local>, catch_up: bool) {
+        let mut entries = HashMap::with_capacity(schedules.len());
+        for spec in schedules {
+            match self.entries.remove(&spec.id) {
+                Some(mut entry) if entry.cron_expr == spec.cron_expr => {
+                    entry.misfire = spec.misfire;
+                    entries.insert(spec.id, entry);
+                }
+                _ => {
+                    if let Ok(schedule) = Schedule::from_str(&spec.cron_expr) {
+                        let from = spec.last_run.filter(|last| catch_up && *last < now).unwrap_or(now);
+                        let next = schedule.after(&from).next();
+                        let entry = Entry {
+                            cron_expr: spec.cron_expr,
+                            schedule,
+                            misfire: spec.misfire,
+                            next,
+                        };
+                        entries.insert(spec.id, entry);
+                    }
+                }
+            }
+        }
+        self.entries = entries;
+    }
+
+    pub fn next_deadline(&self) -> Option<DateTime<Local>> {
+        self.entries.values().filter_map(|e| e.next).min()
+    }
+
+    /// Tasks whose fire time has come, with the runs each one owes, sorted by id.
+    /// Every task is advanced to its next occurrence after `now`. Occurrences more
+    /// than `MISFIRE_GRACE` late are settled by the task's `MisfirePolicy`.
+    pub fn take_due(&mut self, now: DateTime<Local>) -> Vec<(String, Vec<Trigger>)> {
+        // The wall clock went backwards: fire times computed before the jump are too far out
+        if self.last_now.is_some_and(|last| now < last) {
+            for entry in self.entries.values_mut() {
+                entry.next = entry.schedule.after(&now).next();
+            }
+        }
+        self.last_now = Some(now);
+
+        let mut due = Vec::new();
+        for (id, entry) in self.entries.iter_mut() {
+            let Some(first) = entry.next.filter(|next| *next <= now) else {
+                continue;
+            };
+            let runs = entry.due_runs(first, now);
+            entry.next = entry.schedule.after(&now).next();
+            if !runs.is_empty() {
+                due.push((id.clone(), runs));
+            }
+        }
+        due.sort_by(|a, b| a.0.cmp(&b.0));
+        due
+    }
+}
+
+/// The scheduler loop: fires due tasks, then sleeps until the earliest next fire time.
+/// `schedules` is re-read whenever `wake` signals a change; missed runs are only caught
+/// up from the first read. `fire` gets each due task with the runs it owes, in order.
+/// Returns once `wake` closes.
+pub fn run(
+    clock: &impl Clock,
+    wake: &Receiver<()>,
+    mut schedules: impl FnMut() -> Vec<ScheduleSpec>,
+    mut fire: impl FnMut(&str, Vec<Trigger>),
+) {
+    let mut wheel = TimerWheel::default();
+    wheel.reload(schedules(), clock.now(), true);
+
+    loop {
+        for (id, runs) in wheel.take_due(clock.now()) {
+            fire(&id, runs);
+        }
+        match clock.sleep_until(wake, wheel.next_deadline()) {
+            Wake::Timeout => {}
+            Wake::Changed => wheel.reload(schedules(), clock.now(), false),
+            Wake::Closed => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::sync::mpsc::{self, Sender};
+
+    type Schedules = Rc<RefCell<Vec<ScheduleSpec>>>;
+    /// At this time, replace the schedule list.
+    type Edit = (DateTime<Local>, Vec<ScheduleSpec>);
+    type Fired = Vec<(DateTime<Local>, String)>;
+    type Sleeps = Vec<Option<DateTime<Local>>>;
+
+    /// Jumps straight to each deadline instead of sleeping. Scripted edits are applied
+    /// (and reported as `Changed`) when time reaches them; the run ends at `end`.
+    struct MockClock {
+        now: RefCell<DateTime<Local>>,
+        end: DateTime<Local>,
+        edits: RefCell<VecDeque<Edit>>,
+        schedules: Schedules,
+        sender: Sender<()>,
+        sleeps: RefCell<Sleeps>,
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Local> {
+            *self.now.borrow()
+        }
+
+        fn sleep_until(&self, wake: &Receiver<()>, deadline: Option<DateTime<Local>>) -> Wake {
+            self.sleeps.borrow_mut().push(deadline);
+            if wake.try_recv().is_ok() {
+                return Wake::Changed;
+            }
+            let next_edit = self.edits.borrow().front().map(|(at, _)| *at);
+            let target = match (deadline, next_edit) {
+                (Some(d), Some(e)) => d.min(e),
+                (d, e) => match d.or(e) {
+                    Some(t) => t,
+                    None => return Wake::Closed,
+                },
+            };
+            if target > self.end {
+                return Wake::Closed;
+            }
+            *self.now.borrow_mut() = target;
+            if next_edit == Some(target) {
+                let (_, schedules) = self.edits.borrow_mut().pop_front().unwrap();
+                *self.schedules.borrow_mut() = schedules;
+                self.sender.send(()).unwrap();
+                return self.sleep_until(wake, deadline);
+            }
+            Wake::Timeout
+        }
+    }
+
+    fn at(h: u32, m: u32, s: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 4, h, m, s).unwrap()
+    }
+
+    fn task(id: &str, cron_expr: &str) -> ScheduleSpec {
+        ScheduleSpec {
+            id: id.to_string(),
+            cron_expr: cron_expr.to_string(),
+            misfire: MisfirePolicy::Skip,
+            last_run: None,
+        }
+    }
+
+    fn ran_at(spec: ScheduleSpec, last_run: DateTime<Local>, misfire: MisfirePolicy) -> ScheduleSpec {
+        ScheduleSpec {
+            misfire,
+            last_run: Some(last_run),
+            ..spec
+        }
+    }
+
+    fn wheel_at(now: DateTime<Local>, spec: ScheduleSpec) -> TimerWheel {
+        let mut wheel = TimerWheel::default();
+        wheel.reload(vec![spec], now, false);
+        wheel
+    }
+
+    /// Runs the loop from `start` to `end` and returns every (time, task id) run,
+    /// once per run owed.
+    fn simulate(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        initial: Vec<ScheduleSpec>,
+        edits: Vec<Edit>,
+    ) -> (Fired, Sleeps) {
+        let (sender, receiver) = mpsc::channel();
+        let schedules: Schedules = Rc::new(RefCell::new(initial));
+        let clock = MockClock {
+            now: RefCell::new(start),
+            end,
+            edits: RefCell::new(edits.into()),
+            schedules: schedules.clone(),
+            sender,
+            sleeps: RefCell::new(Vec::new()),
+        };
+        let fired = RefCell::new(Vec::new());
+        run(&clock, &receiver, || schedules.borrow().clone(), |id, runs| {
+            for _ in runs {
+                fired.borrow_mut().push((clock.now(), id.to_string()));
+            }
+        });
+        (fired.into_inner(), clock.sleeps.into_inner())
+    }
+
+    #[test]
+    fn fires_once_per_occurrence() {
+        let (fired, _) = simulate(at(10, 0, 30), at(10, 5, 0), vec![task("a", "0 * * * * *")], vec![]);
+        let times: Vec<_> = fired.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, vec![at(10, 1, 0), at(10, 2, 0), at(10, 3, 0), at(10, 4, 0), at(10, 5, 0)]);
+    }
+
+    #[test]
+    fn sleeps_until_earliest_task() {
+        let (fired, sleeps) = simulate(
+            at(10, 0, 0),
+            at(10, 0, 29),
+            vec![task("slow", "*/20 * * * * *"), task("fast", "*/15 * * * * *")],
+            vec![],
+        );
+        assert_eq!(
+            fired,
+            vec![(at(10, 0, 15), "fast".to_string()), (at(10, 0, 20), "slow".to_string())]
+        );
+        // No polling in between: one sleep per fire time, plus the final one past `end`
+        assert_eq!(sleeps, vec![Some(at(10, 0, 15)), Some(at(10, 0, 20)), Some(at(10, 0, 30))]);
+    }
+
+    #[test]
+    fn tasks_due_together_fire_together() {
+        let (fired, _) = simulate(
+            at(10, 0, 0),
+            at(10, 1, 0),
+            vec![task("b", "0 * * * * *"), task("a", "0 * * * * *")],
+            vec![],
+        );
+        assert_eq!(fired, vec![(at(10, 1, 0), "a".to_string()), (at(10, 1, 0), "b".to_string())]);
+    }
+
+    #[test]
+    fn invalid_expressions_are_ignored() {
+        let (fired, sleeps) = simulate(at(10, 0, 0), at(11, 0, 0), vec![task("bad", "not a cron")], vec![]);
+        assert!(fired.is_empty());
+        assert_eq!(sleeps, vec![None]);
+    }
+
+    #[test]
+    fn added_task_wakes_the_loop() {
+        let (fired, _) = simulate(
+            at(10, 0, 0),
+            at(10, 0, 10),
+            vec![],
+            vec![(at(10, 0, 5), vec![task("new", "* * * * * *")])],
+        );
+        let times: Vec<_> = fired.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, (6..=10).map(|s| at(10, 0, s)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn deleted_task_stops_firing() {
+        let (fired, _) = simulate(
+            at(10, 0, 0),
+            at(10, 10, 0),
+            vec![task("a", "0 * * * * *")],
+            vec![(at(10, 2, 30), vec![])],
+        );
+        assert_eq!(fired.len(), 2);
+    }
+
+    #[test]
+    fn edited_expression_is_rescheduled() {
+        let (fired, _) = simulate(
+            at(10, 0, 0),
+            at(12, 0, 0),
+            vec![task("a", "0 0 * * * *")],
+            vec![(at(10, 0, 30), vec![task("a", "0 30 * * * *")])],
+        );
+        let times: Vec<_> = fired.iter().map(|(t, _)| *t).collect();
+        assert_eq!(times, vec![at(10, 30, 0), at(11, 30, 0)]);
+    }
+
+    #[test]
+    fn unchanged_task_keeps_its_fire_time_across_reloads() {
+        let (fired, _) = simulate(
+            at(10, 0, 0),
+            at(10, 1, 0),
+            vec![task("a", "0 * * * * *")],
+            vec![(at(10, 0, 20), vec![task("a", "0 * * * * *"), task("b", "30 * * * * *")])],
+        );
+        assert_eq!(fired, vec![(at(10, 0, 30), "b".to_string()), (at(10, 1, 0), "a".to_string())]);
+    }
+
+    #[test]
+    fn late_wake_within_grace_runs_on_time() {
+        let mut wheel = wheel_at(at(10, 0, 0), task("a", "0 * * * * *"));
+        // Woken ten minutes late, e.g. after the machine slept; 10:10:00 is still on time
+        assert_eq!(wheel.take_due(at(10, 10, 5)), vec![("a".to_string(), vec![Trigger::Cron])]);
+        assert_eq!(wheel.next_deadline(), Some(at(10, 11, 0)));
+        assert!(wheel.take_due(at(10, 10, 6)).is_empty());
+    }
+
+    #[test]
+    fn skip_policy_drops_missed_runs() {
+        let mut wheel = wheel_at(at(9, 30, 0), task("a", "0 0 * * * *"));
+        assert!(wheel.take_due(at(12, 30, 0)).is_empty());
+        assert_eq!(wheel.next_deadline(), Some(at(13, 0, 0)));
+    }
+
+    #[test]
+    fn run_once_policy_catches_up_once() {
+        let spec = ran_at(task("a", "0 0 * * * *"), at(9, 0, 0), MisfirePolicy::RunOnce);
+        let mut wheel = wheel_at(at(9, 30, 0), spec);
+        assert_eq!(wheel.take_due(at(12, 30, 0)), vec![("a".to_string(), vec![Trigger::CatchUp])]);
+        assert_eq!(wheel.next_deadline(), Some(at(13, 0, 0)));
+    }
+
+    #[test]
+    fn run_once_policy_merges_with_on_time_run() {
+        let spec = ran_at(task("a", "0 0 * * * *"), at(9, 0, 0), MisfirePolicy::RunOnce);
+        let mut wheel = wheel_at(at(9, 30, 0), spec);
+        assert_eq!(wheel.take_due(at(12, 0, 10)), vec![("a".to_string(), vec![Trigger::Cron])]);
+    }
+
+    #[test]
+    fn run_all_policy_runs_each_missed_occurrence_up_to_cap() {
+        let policy = MisfirePolicy::RunAll { max_runs: 5 };
+        let mut wheel = wheel_at(at(9, 30, 0), ran_at(task("a", "0 0 * * * *"), at(9, 0, 0), policy));
+        assert_eq!(wheel.take_due(at(12, 30, 0)), vec![("a".to_string(), vec![Trigger::CatchUp; 3])]);
+
+        let policy = MisfirePolicy::RunAll { max_runs: 2 };
+        let mut wheel = wheel_at(at(9, 30, 0), ran_at(task("a", "0 0 * * * *"), at(9, 0, 0), policy));
+        assert_eq!(
+            wheel.take_due(at(12, 0, 5)),
+            vec![("a".to_string(), vec![Trigger::CatchUp, Trigger::CatchUp, Trigger::Cron])]
+        );
+    }
+
+    #[test]
+    fn startup_catches_up_from_last_run() {
+        let yesterday = at(1, 0, 0).checked_sub_days(chrono::Days::new(1)).unwrap();
+        let spec = ran_at(task("backup", "0 0 2 * * *"), yesterday, MisfirePolicy::RunOnce);
+        let (fired, _) = simulate(at(9, 0, 0), at(9, 0, 1), vec![spec], vec![]);
+        assert_eq!(fired, vec![(at(9, 0, 0), "backup".to_string())]);
+    }
+
+    #[test]
+    fn startup_run_all_is_capped() {
+        let policy = MisfirePolicy::RunAll { max_runs: 3 };
+        let spec = ran_at(task("a", "0 0 * * * *"), at(1, 30, 0), policy);
+        let (fired, _) = simulate(at(9, 0, 30), at(9, 0, 31), vec![spec], vec![]);
+        // Three of the seven missed runs (02:00 to 08:00), then 09:00, still within the grace period
+        assert_eq!(fired.len(), 4);
+    }
+
+    #[test]
+    fn startup_skip_policy_waits_for_next_occurrence() {
+        let spec = ran_at(task("a", "0 0 2 * * *"), at(1, 0, 0), MisfirePolicy::Skip);
+        let (fired, sleeps) = simulate(at(9, 0, 0), at(9, 0, 1), vec![spec], vec![]);
+        assert!(fired.is_empty());
+        assert_eq!(sleeps, vec![Some(at(2, 0, 0).checked_add_days(chrono::Days::new(1)).unwrap())]);
+    }
+
+    #[test]
+    fn tasks_added_later_are_not_caught_up() {
+        let spec = ran_at(task("a", "0 0 2 * * *"), at(1, 0, 0), MisfirePolicy::RunOnce);
+        let (fired, _) = simulate(at(9, 0, 0), at(10, 0, 0), vec![], vec![(at(9, 30, 0), vec![spec])]);
+        assert!(fired.is_empty());
+    }
+
+    #[test]
+    fn clock_moving_back_reschedules() {
+        let mut wheel = wheel_at(at(10, 0, 0), task("a", "0 0 * * * *"));
+        assert_eq!(wheel.next_deadline(), Some(at(11, 0, 0)));
+        assert!(wheel.take_due(at(10, 0, 1)).is_empty());
+        assert!(wheel.take_due(at(8, 10, 0)).is_empty());
+        assert_eq!(wheel.next_deadline(), Some(at(9, 0, 0)));
+    }
+}
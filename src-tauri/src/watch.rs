@@ -0,0 +1,387 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::monitor::MonitorState;
+use crate::query::{Field, Query};
+use crate::run_history::RunTrigger;
+use crate::scheduler::{self, SchedulerState};
+use crate::ProcessInfo;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchCondition {
+    CpuAbove { percent: f32 },
+    MemoryAbove { bytes: u64 },
+    Exited,
+    Appeared,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Selects the processes to watch, in the `get_processes` query language (e.g. `name="java"`).
+    pub filter: String,
+    pub condition: WatchCondition,
+    /// How long a CPU or memory threshold must be exceeded before alerting.
+    #[serde(default)]
+    pub for_secs: u64,
+    /// Scheduler task to run when the rule fires.
+    #[serde(default)]
+    pub task_id: Option<String>,
+    pub enabled: bool,
+}
+
+/// Payload of the `process-alert` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessAlert {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub pid: u32,
+    pub process_name: String,
+    pub condition: WatchCondition,
+    pub message: String,
+    /// Unix time in milliseconds.
+    pub ts: i64,
+}
+
+pub struct WatchEngine {
+    pub rules: Mutex<Vec<WatchRule>>,
+    pub data_path: PathBuf,
+    pub monitor: MonitorState,
+    pub scheduler: SchedulerState,
+    pub app_handle: AppHandle,
+}
+
+#[derive(Clone)]
+pub struct WatchState(pub Arc<WatchEngine>);
+
+/// Evaluation state of one rule, rebuilt whenever the rule is edited.
+struct RuleRuntime {
+    rule: WatchRule,
+    query: Option<Query>,
+    /// PID -> when its threshold was first exceeded.
+    over_since: HashMap<u32, i64>,
+    /// PIDs already alerted for the current threshold breach.
+    fired: HashSet<u32>,
+    /// Matching PIDs and names from the previous sample; `None` until the first one.
+    previous: Option<HashMap<u32, String>>,
+}
+
+impl RuleRuntime {
+    fn new(rule: &WatchRule) -> Self {
+        RuleRuntime {
+            rule: rule.clone(),
+            query: Query::parse(&rule.filter).ok(),
+            over_since: HashMap::new(),
+            fired: HashSet::new(),
+            previous: None,
+        }
+    }
+
+    fn evaluate(&mut self, ts: i64, processes: &[ProcessInfo]) -> Vec<ProcessAlert> {
+        let Some(query) = &self.query else {
+            return Vec::new();
+        };
+        let matching: Vec<&ProcessInfo> = processes.iter().filter(|p| query.matches(p)).collect();
+        let current: HashMap<u32, String> = matching.iter().map(|p| (p.pid, p.name.clone())).collect();
+        let rule = &self.rule;
+        let alert = |pid: u32, process_name: &str, message: String| ProcessAlert {
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            pid,
+            process_name: process_name.to_string(),
+            condition: rule.condition.clone(),
+            message,
+            ts,
+        };
+
+        let mut alerts = Vec::new();
+        match &rule.condition {
+            WatchCondition::CpuAbove { .. } | WatchCondition::MemoryAbove { .. } => {
+                self.over_since.retain(|pid, _| current.contains_key(pid));
+                self.fired.retain(|pid| current.contains_key(pid));
+
+                for p in &matching {
+                    let over = match rule.condition {
+                        WatchCondition::CpuAbove { percent } => p.cpu_usage > percent,
+                        WatchCondition::MemoryAbove { bytes } => p.memory > bytes,
+                        _ => false,
+                    };
+                    if !over {
+                        // Re-arm once the process drops back under the threshold
+                        self.over_since.remove(&p.pid);
+                        self.fired.remove(&p.pid);
+                        continue;
+                    }
+                    let since = *self.over_since.entry(p.pid).or_insert(ts);
+                    if !self.fired.contains(&p.pid) && ts - since >= rule.for_secs as i64 * 1000 {
+                        self.fired.insert(p.pid);
+                        let usage = match rule.condition {
+                            WatchCondition::CpuAbove { .. } => format!("{:.1}% CPU", p.cpu_usage),
+                            _ => format!("{:.1} MB of memory", p.memory as f64 / (1024.0 * 1024.0)),
+                        };
+                        alerts.push(alert(
+                            p.pid,
+                            &p.name,
+                            format!("{} ({}) is using {} for {}s", p.name, p.pid, usage, (ts - since) / 1000),
+                        ));
+                    }
+                }
+            }
+            WatchCondition::Exited => {
+                if let Some(previous) = &self.previous {
+                    // A process that merely stopped matching (e.g. its CPU dropped) is still running
+                    let alive: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+                    for (pid, name) in previous.iter().filter(|(pid, _)| !alive.contains(pid)) {
+                        alerts.push(alert(*pid, name, format!("{} ({}) exited", name, pid)));
+                    }
+                }
+            }
+            WatchCondition::Appeared => {
+                if let Some(previous) = &self.previous {
+                    for (pid, name) in current.iter().filter(|(pid, _)| !previous.contains_key(pid)) {
+                        alerts.push(alert(*pid, name, format!("{} ({}) started", name, pid)));
+                    }
+                }
+            }
+        }
+
+        self.previous = Some(current);
+        alerts
+    }
+}
+
+impl WatchEngine {
+    pub fn new(app_handle: AppHandle, monitor: MonitorState, scheduler: SchedulerState) -> Self {
+        // Kept next to the scheduler's tasks.json
+        let data_path = scheduler.0.data_path.with_file_name("watch_rules.json");
+        let rules = fs::read_to_string(&data_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        WatchEngine {
+            rules: Mutex::new(rules),
+            data_path,
+            monitor,
+            scheduler,
+            app_handle,
+        }
+    }
+
+    pub fn save(&self) {
+        let rules = self.rules.lock().unwrap();
+        let content = serde_json::to_string_pretty(&*rules).unwrap_or_default();
+        let _ = fs::write(&self.data_path, content);
+    }
+
+    fn fire(&self, alert: &ProcessAlert, task_id: Option<&str>) {
+        let _ = self.app_handle.emit("process-alert", alert);
+        if let Some(tray) = self.app_handle.tray_by_id("tray") {
+            let _ = tray.set_tooltip(Some(format!("aTools - {}", alert.message)));
+        }
+
+        if let Some(task_id) = task_id {
+            // Don't stack runs if the rule keeps firing while the task is still going
+            if self.scheduler.0.running_tasks.lock().unwrap().contains_key(task_id) {
+                return;
+            }
+            let scheduler = self.scheduler.clone();
+            let task_id = task_id.to_string();
+            thread::spawn(move || {
+                scheduler::run_task_internal(&scheduler, &task_id, RunTrigger::Watch);
+            });
+        }
+    }
+}
+
+pub fn init(app_handle: AppHandle, monitor: MonitorState, scheduler: SchedulerState) -> WatchState {
+    let state = WatchState(Arc::new(WatchEngine::new(app_handle, monitor, scheduler)));
+
+    // Start background thread
+    let thread_state = state.clone();
+    // Subscribed before the thread starts so the first sample is not missed
+    let samples = state.0.monitor.0.subscribe();
+    thread::spawn(move || {
+        let engine = &thread_state.0;
+        let mut runtimes: HashMap<String, RuleRuntime> = HashMap::new();
+
+        // Woken by the monitor after each refresh, so every sample is evaluated exactly once
+        for sample in samples {
+            let rules: Vec<WatchRule> = engine.rules.lock().unwrap().iter().filter(|r| r.enabled).cloned().collect();
+            runtimes.retain(|id, _| rules.iter().any(|r| &r.id == id));
+            if rules.is_empty() {
+                continue;
+            }
+
+            for rule in &rules {
+                let runtime = runtimes.entry(rule.id.clone()).or_insert_with(|| RuleRuntime::new(rule));
+                if runtime.rule != *rule {
+                    *runtime = RuleRuntime::new(rule);
+                }
+                for alert in runtime.evaluate(sample.ts, &sample.processes) {
+                    engine.fire(&alert, rule.task_id.as_deref());
+                }
+            }
+        }
+    });
+
+    state
+}
+
+fn validate(rule: &WatchRule) -> Result<(), String> {
+    let query = Query::parse(&rule.filter).map_err(|e| format!("Invalid filter: {}", e))?;
+    // Monitor samples skip the socket scan, so a port condition would never match
+    if query.uses(Field::Ports) {
+        return Err("Watch rules can't filter on ports".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_watch_rules(state: State<'_, WatchState>) -> Vec<WatchRule> {
+    state.0.rules.lock().unwrap().clone()
+}
+
+/// Stores a new rule and returns it with its generated id.
+#[tauri::command]
+pub fn add_watch_rule(mut rule: WatchRule, state: State<'_, WatchState>) -> Result<WatchRule, String> {
+    validate(&rule)?;
+    rule.id = uuid::Uuid::new_v4().to_string();
+    state.0.rules.lock().unwrap().push(rule.clone());
+    state.0.save();
+    Ok(rule)
+}
+
+#[tauri::command]
+pub fn update_watch_rule(rule: WatchRule, state: State<'_, WatchState>) -> Result<(), String> {
+    validate(&rule)?;
+    let mut rules = state.0.rules.lock().unwrap();
+    let existing = rules
+        .iter_mut()
+        .find(|r| r.id == rule.id)
+        .ok_or_else(|| format!("Rule {} not found", rule.id))?;
+    *existing = rule;
+    drop(rules);
+    state.0.save();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_watch_rule(id: String, state: State<'_, WatchState>) {
+    state.0.rules.lock().unwrap().retain(|r| r.id != id);
+    state.0.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(filter: &str, condition: WatchCondition, for_secs: u64) -> WatchRule {
+        WatchRule {
+            id: "r".to_string(),
+            name: "rule".to_string(),
+            filter: filter.to_string(),
+            condition,
+            for_secs,
+            task_id: None,
+            enabled: true,
+        }
+    }
+
+    fn process(pid: u32, name: &str, cpu_usage: f32) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid: None,
+            name: name.to_string(),
+            exe: String::new(),
+            cmd: String::new(),
+            memory: 0,
+            cpu_usage,
+            status: "Run".to_string(),
+            user: None,
+            ports: Vec::new(),
+            sockets: Vec::new(),
+            window_handle: None,
+        }
+    }
+
+    /// Feeds one sample per second and returns the PIDs alerted at each.
+    fn run(rule: &WatchRule, samples: &[Vec<ProcessInfo>]) -> Vec<Vec<u32>> {
+        let mut runtime = RuleRuntime::new(rule);
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, processes)| {
+                let mut pids: Vec<u32> =
+                    runtime.evaluate(i as i64 * 1000, processes).iter().map(|a| a.pid).collect();
+                pids.sort();
+                pids
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cpu_alert_rearms_after_dropping_below_the_threshold() {
+        let rule = rule("name=java", WatchCondition::CpuAbove { percent: 50.0 }, 0);
+        let alerts = run(
+            &rule,
+            &[
+                vec![process(1, "java", 90.0)],
+                vec![process(1, "java", 90.0)],
+                vec![process(1, "java", 10.0)],
+                vec![process(1, "java", 90.0)],
+            ],
+        );
+        assert_eq!(alerts, [vec![1], vec![], vec![], vec![1]]);
+    }
+
+    #[test]
+    fn threshold_must_hold_for_secs() {
+        let rule = rule("", WatchCondition::CpuAbove { percent: 50.0 }, 2);
+        let busy = vec![process(1, "a", 90.0)];
+        let alerts = run(&rule, &[busy.clone(), busy.clone(), busy.clone(), busy.clone()]);
+        assert_eq!(alerts, [vec![], vec![], vec![1], vec![]]);
+
+        // A dip resets the clock
+        let idle = vec![process(1, "a", 0.0)];
+        let alerts = run(&rule, &[busy.clone(), busy.clone(), idle, busy.clone(), busy.clone(), busy]);
+        assert_eq!(alerts, [vec![], vec![], vec![], vec![], vec![], vec![1]]);
+    }
+
+    #[test]
+    fn first_sample_only_sets_the_baseline() {
+        let running = vec![process(1, "a", 0.0), process(2, "b", 0.0)];
+        let appeared = rule("", WatchCondition::Appeared, 0);
+        assert_eq!(run(&appeared, std::slice::from_ref(&running)), [Vec::<u32>::new()]);
+        let exited = rule("", WatchCondition::Exited, 0);
+        assert_eq!(run(&exited, &[running, Vec::new()]), [vec![], vec![1, 2]]);
+    }
+
+    #[test]
+    fn exited_ignores_processes_that_only_stopped_matching() {
+        let rule = rule("cpu>50", WatchCondition::Exited, 0);
+        let alerts = run(
+            &rule,
+            &[
+                vec![process(1, "a", 90.0), process(2, "b", 90.0)],
+                vec![process(1, "a", 10.0)],
+            ],
+        );
+        assert_eq!(alerts, [vec![], vec![2]]);
+    }
+
+    #[test]
+    fn port_filters_are_rejected() {
+        assert!(validate(&rule("name=nginx", WatchCondition::Exited, 0)).is_ok());
+        assert!(validate(&rule("name=nginx and port=8080", WatchCondition::Exited, 0)).is_err());
+        assert!(validate(&rule("not ports>1024", WatchCondition::Exited, 0)).is_err());
+    }
+}
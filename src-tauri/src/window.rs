@@ -0,0 +1,669 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{mpsc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Upper bound on how long `get_processes` waits for window handles before listing without them.
+const HANDLES_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Serialize, Clone)]
+pub struct WindowInfo {
+    /// `HWND` on Windows, X11 window id on Linux, formatted as `0x...`.
+    pub handle: String,
+    pub pid: u32,
+    pub title: String,
+    pub class: String,
+    /// Screen coordinates of the top-left corner.
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    pub minimized: bool,
+}
+
+fn format_handle(handle: usize) -> String {
+    format!("0x{:X}", handle)
+}
+
+fn parse_handle(handle: &str) -> Result<usize, String> {
+    let digits = handle
+        .strip_prefix("0x")
+        .or_else(|| handle.strip_prefix("0X"))
+        .unwrap_or(handle);
+    match usize::from_str_radix(digits, 16) {
+        Ok(0) | Err(_) => Err(format!("Invalid window handle: {}", handle)),
+        Ok(value) => Ok(value),
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{format_handle, WindowInfo};
+    use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetClassNameW, GetWindowRect, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+        IsIconic, IsWindow, IsWindowVisible, PostMessageW, SetForegroundWindow, SetWindowPos, ShowWindow,
+        HWND_NOTOPMOST, HWND_TOPMOST, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE, SW_MINIMIZE, SW_RESTORE, WM_CLOSE,
+    };
+
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let handles = &mut *(lparam as *mut Vec<HWND>);
+        handles.push(hwnd);
+        1
+    }
+
+    unsafe fn describe(hwnd: HWND) -> WindowInfo {
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+
+        let len = GetWindowTextLengthW(hwnd).max(0) as usize;
+        let mut title = vec![0u16; len + 1];
+        let copied = GetWindowTextW(hwnd, title.as_mut_ptr(), title.len() as i32).max(0) as usize;
+
+        // Window class names are capped at 256 characters
+        let mut class = [0u16; 256];
+        let class_len = GetClassNameW(hwnd, class.as_mut_ptr(), class.len() as i32).max(0) as usize;
+
+        let mut rect: RECT = std::mem::zeroed();
+        GetWindowRect(hwnd, &mut rect);
+
+        WindowInfo {
+            handle: format_handle(hwnd as usize),
+            pid,
+            title: String::from_utf16_lossy(&title[..copied]),
+            class: String::from_utf16_lossy(&class[..class_len]),
+            x: rect.left,
+            y: rect.top,
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+            visible: IsWindowVisible(hwnd) != 0,
+            minimized: IsIconic(hwnd) != 0,
+        }
+    }
+
+    pub fn enumerate() -> Result<Vec<WindowInfo>, String> {
+        let mut handles: Vec<HWND> = Vec::new();
+        unsafe {
+            if EnumWindows(Some(collect), &mut handles as *mut Vec<HWND> as LPARAM) == 0 {
+                return Err(std::io::Error::last_os_error().to_string());
+            }
+            Ok(handles.into_iter().map(|hwnd| describe(hwnd)).collect())
+        }
+    }
+
+    /// Nothing to keep between enumerations on Windows.
+    #[derive(Default)]
+    pub struct Cache;
+
+    pub fn enumerate_cached(_cache: &mut Cache) -> Result<Vec<WindowInfo>, String> {
+        enumerate()
+    }
+
+    fn window(handle: usize) -> Result<HWND, String> {
+        let hwnd = handle as HWND;
+        if unsafe { IsWindow(hwnd) } == 0 {
+            return Err(format!("Window {} not found", format_handle(handle)));
+        }
+        Ok(hwnd)
+    }
+
+    pub fn focus(handle: usize) -> Result<(), String> {
+        let hwnd = window(handle)?;
+        unsafe {
+            if IsIconic(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+            }
+            // Refused when another app holds the foreground lock; the taskbar button flashes instead
+            if SetForegroundWindow(hwnd) == 0 {
+                return Err("Windows refused to bring the window to the foreground".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn minimize(handle: usize) -> Result<(), String> {
+        let hwnd = window(handle)?;
+        // The return value is the previous visibility, not success
+        unsafe { ShowWindow(hwnd, SW_MINIMIZE) };
+        Ok(())
+    }
+
+    pub fn close(handle: usize) -> Result<(), String> {
+        let hwnd = window(handle)?;
+        if unsafe { PostMessageW(hwnd, WM_CLOSE, 0, 0) } == 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    pub fn set_topmost(handle: usize, topmost: bool) -> Result<(), String> {
+        let hwnd = window(handle)?;
+        let insert_after = if topmost { HWND_TOPMOST } else { HWND_NOTOPMOST };
+        let flags = SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE;
+        if unsafe { SetWindowPos(hwnd, insert_after, 0, 0, 0, 0, flags) } == 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{format_handle, WindowInfo};
+    use x11rb::connection::Connection;
+    use x11rb::cookie::VoidCookie;
+    use x11rb::errors::ConnectionError;
+    use x11rb::protocol::xproto::{
+        AtomEnum, ClientMessageEvent, ConfigureWindowAux, ConnectionExt, EventMask, InputFocus, MapState, StackMode,
+        Window,
+    };
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::CURRENT_TIME;
+
+    x11rb::atom_manager! {
+        Atoms: AtomsCookie {
+            _NET_SUPPORTED,
+            _NET_CLIENT_LIST,
+            _NET_ACTIVE_WINDOW,
+            _NET_CLOSE_WINDOW,
+            _NET_WM_PID,
+            _NET_WM_NAME,
+            _NET_WM_STATE,
+            _NET_WM_STATE_HIDDEN,
+            _NET_WM_STATE_ABOVE,
+            WM_STATE,
+            WM_CHANGE_STATE,
+            WM_PROTOCOLS,
+            WM_DELETE_WINDOW,
+            UTF8_STRING,
+        }
+    }
+
+    /// ICCCM `WM_STATE` value for iconified windows.
+    const ICONIC_STATE: u32 = 3;
+    /// EWMH source indication for requests coming from a pager or taskbar.
+    const SOURCE_PAGER: u32 = 2;
+    const NET_WM_STATE_REMOVE: u32 = 0;
+    const NET_WM_STATE_ADD: u32 = 1;
+
+    /// Waits for a request without a reply so X errors (e.g. `BadWindow`) are reported.
+    fn checked(request: Result<VoidCookie<'_, RustConnection>, ConnectionError>) -> Result<(), String> {
+        request.map_err(|e| e.to_string())?.check().map_err(|e| e.to_string())
+    }
+
+    struct Display {
+        conn: RustConnection,
+        root: Window,
+        atoms: Atoms,
+    }
+
+    impl Display {
+        fn open() -> Result<Self, String> {
+            let (conn, screen) = x11rb::connect(None).map_err(|e| format!("No X11 display available: {}", e))?;
+            let root = conn.setup().roots[screen].root;
+            let atoms = Atoms::new(&conn)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|e| e.to_string())?;
+            Ok(Display { conn, root, atoms })
+        }
+
+        /// Whether an EWMH-compliant window manager is running to act on our requests.
+        fn has_window_manager(&self) -> bool {
+            !property32(&self.conn, self.root, self.atoms._NET_SUPPORTED, AtomEnum::ATOM.into()).is_empty()
+        }
+
+        fn window(&self, handle: usize) -> Result<Window, String> {
+            let window = Window::try_from(handle).map_err(|_| format!("Window {} not found", format_handle(handle)))?;
+            self.conn
+                .get_window_attributes(window)
+                .map_err(|e| e.to_string())?
+                .reply()
+                .map_err(|_| format!("Window {} not found", format_handle(handle)))?;
+            Ok(window)
+        }
+
+        /// Client message addressed to the window manager via the root window.
+        fn send_to_wm(&self, window: Window, kind: u32, data: [u32; 5]) -> Result<(), String> {
+            let event = ClientMessageEvent::new(32, window, kind, data);
+            let mask = EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY;
+            checked(self.conn.send_event(false, self.root, mask, event))
+        }
+
+        fn raise(&self, window: Window) -> Result<(), String> {
+            let aux = ConfigureWindowAux::new().stack_mode(StackMode::ABOVE);
+            checked(self.conn.configure_window(window, &aux))
+        }
+    }
+
+    fn property(conn: &RustConnection, window: Window, atom: u32, kind: u32) -> Option<Vec<u8>> {
+        let reply = conn.get_property(false, window, atom, kind, 0, u32::MAX / 4).ok()?.reply().ok()?;
+        (reply.type_ != u32::from(AtomEnum::NONE)).then_some(reply.value)
+    }
+
+    fn property32(conn: &RustConnection, window: Window, atom: u32, kind: u32) -> Vec<u32> {
+        conn.get_property(false, window, atom, kind, 0, u32::MAX / 4)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().map(|values| values.collect()))
+            .unwrap_or_default()
+    }
+
+    /// Managed top-level windows, or the root's children when no EWMH window manager is running.
+    fn top_level_windows(conn: &RustConnection, root: Window, atoms: &Atoms) -> Result<Vec<Window>, String> {
+        let managed = property32(conn, root, atoms._NET_CLIENT_LIST, AtomEnum::WINDOW.into());
+        if !managed.is_empty() {
+            return Ok(managed);
+        }
+        let tree = conn
+            .query_tree(root)
+            .map_err(|e| e.to_string())?
+            .reply()
+            .map_err(|e| e.to_string())?;
+        Ok(tree.children)
+    }
+
+    fn describe(conn: &RustConnection, root: Window, atoms: &Atoms, window: Window) -> Option<WindowInfo> {
+        let attributes = conn.get_window_attributes(window).ok()?.reply().ok()?;
+        // Menus and tooltips are not real top-level windows
+        if attributes.override_redirect {
+            return None;
+        }
+        // Windows that don't advertise a PID can't be attributed to a process
+        let pid = *property32(conn, window, atoms._NET_WM_PID, AtomEnum::CARDINAL.into()).first()?;
+
+        let title = property(conn, window, atoms._NET_WM_NAME, atoms.UTF8_STRING)
+            .or_else(|| property(conn, window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into()))
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+            .unwrap_or_default();
+
+        // WM_CLASS is "instance\0class\0"
+        let class = property(conn, window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+            .and_then(|bytes| {
+                bytes
+                    .split(|b| *b == 0)
+                    .nth(1)
+                    .map(|class| String::from_utf8_lossy(class).into_owned())
+            })
+            .unwrap_or_default();
+
+        let geometry = conn.get_geometry(window).ok()?.reply().ok()?;
+        let origin = conn.translate_coordinates(window, root, 0, 0).ok()?.reply().ok()?;
+
+        let hidden = property32(conn, window, atoms._NET_WM_STATE, AtomEnum::ATOM.into())
+            .contains(&atoms._NET_WM_STATE_HIDDEN);
+        let iconic = property32(conn, window, atoms.WM_STATE, atoms.WM_STATE).first() == Some(&ICONIC_STATE);
+
+        Some(WindowInfo {
+            handle: format_handle(window as usize),
+            pid,
+            title,
+            class,
+            x: origin.dst_x.into(),
+            y: origin.dst_y.into(),
+            width: geometry.width.into(),
+            height: geometry.height.into(),
+            visible: attributes.map_state == MapState::VIEWABLE,
+            minimized: hidden || iconic,
+        })
+    }
+
+    fn list(display: &Display) -> Result<Vec<WindowInfo>, String> {
+        let Display { conn, root, atoms } = display;
+        let windows = top_level_windows(conn, *root, atoms)?;
+        Ok(windows
+            .into_iter()
+            .filter_map(|window| describe(conn, *root, atoms, window))
+            .collect())
+    }
+
+    pub fn enumerate() -> Result<Vec<WindowInfo>, String> {
+        list(&Display::open()?)
+    }
+
+    /// Display connection reused across enumerations.
+    #[derive(Default)]
+    pub struct Cache(Option<Display>);
+
+    pub fn enumerate_cached(cache: &mut Cache) -> Result<Vec<WindowInfo>, String> {
+        let display = match cache.0.take() {
+            Some(display) => display,
+            None => Display::open()?,
+        };
+        let windows = list(&display)?;
+        // Dropped on error so a broken connection is reopened next time
+        cache.0 = Some(display);
+        Ok(windows)
+    }
+
+    pub fn focus(handle: usize) -> Result<(), String> {
+        let display = Display::open()?;
+        let window = display.window(handle)?;
+        if display.has_window_manager() {
+            return display.send_to_wm(window, display.atoms._NET_ACTIVE_WINDOW, [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0]);
+        }
+        // Without a window manager nobody else will map, raise or focus it
+        checked(display.conn.map_window(window))?;
+        display.raise(window)?;
+        checked(display.conn.set_input_focus(InputFocus::PARENT, window, CURRENT_TIME))
+    }
+
+    pub fn minimize(handle: usize) -> Result<(), String> {
+        let display = Display::open()?;
+        let window = display.window(handle)?;
+        if display.has_window_manager() {
+            return display.send_to_wm(window, display.atoms.WM_CHANGE_STATE, [ICONIC_STATE, 0, 0, 0, 0]);
+        }
+        checked(display.conn.unmap_window(window))
+    }
+
+    pub fn close(handle: usize) -> Result<(), String> {
+        let display = Display::open()?;
+        let window = display.window(handle)?;
+        let atoms = &display.atoms;
+
+        // Ask the client itself when it speaks WM_DELETE_WINDOW, so this works even without a WM
+        let protocols = property32(&display.conn, window, atoms.WM_PROTOCOLS, AtomEnum::ATOM.into());
+        if protocols.contains(&atoms.WM_DELETE_WINDOW) {
+            let event = ClientMessageEvent::new(
+                32,
+                window,
+                atoms.WM_PROTOCOLS,
+                [atoms.WM_DELETE_WINDOW, CURRENT_TIME, 0, 0, 0],
+            );
+            return checked(display.conn.send_event(false, window, EventMask::NO_EVENT, event));
+        }
+        if display.has_window_manager() {
+            return display.send_to_wm(window, atoms._NET_CLOSE_WINDOW, [CURRENT_TIME, SOURCE_PAGER, 0, 0, 0]);
+        }
+        Err("Window does not support being closed gracefully".to_string())
+    }
+
+    pub fn set_topmost(handle: usize, topmost: bool) -> Result<(), String> {
+        let display = Display::open()?;
+        let window = display.window(handle)?;
+        if display.has_window_manager() {
+            let action = if topmost { NET_WM_STATE_ADD } else { NET_WM_STATE_REMOVE };
+            let atoms = &display.atoms;
+            return display.send_to_wm(
+                window,
+                atoms._NET_WM_STATE,
+                [action, atoms._NET_WM_STATE_ABOVE, 0, SOURCE_PAGER, 0],
+            );
+        }
+        // No WM to keep it on top; the best we can do is raise it once
+        if topmost {
+            display.raise(window)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::WindowInfo;
+
+    pub fn enumerate() -> Result<Vec<WindowInfo>, String> {
+        Err("Window enumeration is not supported on this platform".to_string())
+    }
+
+    #[derive(Default)]
+    pub struct Cache;
+
+    pub fn enumerate_cached(_cache: &mut Cache) -> Result<Vec<WindowInfo>, String> {
+        enumerate()
+    }
+
+    pub fn focus(_handle: usize) -> Result<(), String> {
+        Err("Window actions are not supported on this platform".to_string())
+    }
+
+    pub fn minimize(_handle: usize) -> Result<(), String> {
+        Err("Window actions are not supported on this platform".to_string())
+    }
+
+    pub fn close(_handle: usize) -> Result<(), String> {
+        Err("Window actions are not supported on this platform".to_string())
+    }
+
+    pub fn set_topmost(_handle: usize, _topmost: bool) -> Result<(), String> {
+        Err("Window actions are not supported on this platform".to_string())
+    }
+}
+
+/// All top-level windows in stacking order (bottom to top on X11, top to bottom on Windows).
+pub fn enumerate_windows() -> Result<Vec<WindowInfo>, String> {
+    platform::enumerate()
+}
+
+/// The window most likely to be a process's main window: the first visible one with
+/// a title, otherwise its first window of any kind.
+fn main_window_handles(windows: Vec<WindowInfo>) -> HashMap<u32, String> {
+    let mut best: HashMap<u32, (bool, String)> = HashMap::new();
+    for window in windows {
+        let preferred = window.visible && !window.title.is_empty();
+        match best.get(&window.pid) {
+            Some((true, _)) => {}
+            Some((false, _)) if !preferred => {}
+            _ => {
+                best.insert(window.pid, (preferred, window.handle));
+            }
+        }
+    }
+    best.into_iter().map(|(pid, (_, handle))| (pid, handle)).collect()
+}
+
+type HandlesReply = mpsc::Sender<HashMap<u32, String>>;
+
+/// The one thread that collects main-window handles. A stalled display then holds up
+/// this thread alone rather than leaving one behind per `get_processes` call.
+fn handles_worker() -> &'static mpsc::Sender<HandlesReply> {
+    static WORKER: OnceLock<mpsc::Sender<HandlesReply>> = OnceLock::new();
+    WORKER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<HandlesReply>();
+        thread::spawn(move || {
+            let mut cache: platform::Cache = Default::default();
+            while let Ok(first) = rx.recv() {
+                // Requests that queued up meanwhile are served by the same enumeration
+                let waiting: Vec<HandlesReply> = std::iter::once(first).chain(rx.try_iter()).collect();
+                let handles = platform::enumerate_cached(&mut cache).map(main_window_handles).unwrap_or_default();
+                for reply in waiting {
+                    let _ = reply.send(handles.clone());
+                }
+            }
+        });
+        tx
+    })
+}
+
+/// Main-window handles being collected by the window worker.
+pub struct PendingHandles {
+    rx: mpsc::Receiver<HashMap<u32, String>>,
+    deadline: Instant,
+}
+
+impl PendingHandles {
+    /// Whatever was collected by the deadline; empty if enumeration failed or is still running.
+    pub fn wait(self) -> HashMap<u32, String> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        self.rx.recv_timeout(remaining).unwrap_or_default()
+    }
+}
+
+/// Asks the window worker for main-window handles so the caller can do other work meanwhile.
+pub fn request_main_window_handles() -> PendingHandles {
+    let (tx, rx) = mpsc::channel();
+    let _ = handles_worker().send(tx);
+    PendingHandles { rx, deadline: Instant::now() + HANDLES_TIMEOUT }
+}
+
+/// Top-level windows, optionally only those owned by `pid`.
+#[tauri::command]
+pub fn list_windows(pid: Option<u32>) -> Result<Vec<WindowInfo>, String> {
+    let mut windows = enumerate_windows()?;
+    if let Some(pid) = pid {
+        windows.retain(|w| w.pid == pid);
+    }
+    Ok(windows)
+}
+
+/// Brings the window to the front and gives it focus, restoring it if minimized.
+#[tauri::command]
+pub fn focus_window(handle: String) -> Result<(), String> {
+    platform::focus(parse_handle(&handle)?)
+}
+
+#[tauri::command]
+pub fn minimize_window(handle: String) -> Result<(), String> {
+    platform::minimize(parse_handle(&handle)?)
+}
+
+/// Asks the window to close, the same as clicking its close button. The application
+/// may prompt the user or ignore the request; use `kill_process` for stuck ones.
+#[tauri::command]
+pub fn close_window(handle: String) -> Result<(), String> {
+    platform::close(parse_handle(&handle)?)
+}
+
+#[tauri::command]
+pub fn set_window_topmost(handle: String, topmost: bool) -> Result<(), String> {
+    platform::set_topmost(parse_handle(&handle)?, topmost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(pid: u32, handle: &str, title: &str, visible: bool) -> WindowInfo {
+        WindowInfo {
+            handle: handle.to_string(),
+            pid,
+            title: title.to_string(),
+            class: String::new(),
+            x: 0,
+            y: 0,
+            width: 100,
+            height: 100,
+            visible,
+            minimized: false,
+        }
+    }
+
+    #[test]
+    fn handles_round_trip() {
+        assert_eq!(parse_handle(&format_handle(0x1c00007)), Ok(0x1c00007));
+        assert_eq!(parse_handle("0X1A"), Ok(0x1a));
+        assert_eq!(parse_handle("1a"), Ok(0x1a));
+        assert!(parse_handle("0x0").is_err());
+        assert!(parse_handle("0xzz").is_err());
+    }
+
+    #[test]
+    fn main_window_is_the_first_visible_titled_one() {
+        let windows = vec![
+            window(1, "0x1", "", true),
+            window(1, "0x2", "hidden", false),
+            window(1, "0x3", "Editor", true),
+            window(1, "0x4", "Later", true),
+            // Nothing better, so the first window wins
+            window(2, "0x5", "", false),
+            window(2, "0x6", "", true),
+        ];
+        let handles = main_window_handles(windows);
+        assert_eq!(handles.get(&1).map(String::as_str), Some("0x3"));
+        assert_eq!(handles.get(&2).map(String::as_str), Some("0x5"));
+    }
+
+    #[test]
+    fn pending_handles_give_up_at_the_deadline() {
+        // A worker stuck on a stalled display never answers
+        let (_stalled, rx) = mpsc::channel();
+        let pending = PendingHandles { rx, deadline: Instant::now() + Duration::from_millis(50) };
+        let started = Instant::now();
+        assert!(pending.wait().is_empty());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
+
+/// Run with an X server, e.g. `xvfb-run cargo test -- --ignored`.
+#[cfg(all(test, target_os = "linux"))]
+mod x11_tests {
+    use super::*;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, CreateWindowAux, PropMode, Window, WindowClass};
+    use x11rb::rust_connection::RustConnection;
+    use x11rb::wrapper::ConnectionExt as _;
+    use x11rb::COPY_DEPTH_FROM_PARENT;
+
+    fn connect() -> (RustConnection, usize) {
+        x11rb::connect(None).expect("these tests need an X server (DISPLAY)")
+    }
+
+    fn atom(conn: &RustConnection, name: &str) -> u32 {
+        conn.intern_atom(false, name.as_bytes()).unwrap().reply().unwrap().atom
+    }
+
+    /// Maps a titled top-level window that claims to belong to `pid`.
+    fn create_window(conn: &RustConnection, screen: usize, pid: u32, title: &str) -> Window {
+        let root = conn.setup().roots[screen].root;
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            root,
+            10,
+            20,
+            200,
+            100,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new(),
+        )
+        .unwrap();
+        let net_wm_pid = atom(conn, "_NET_WM_PID");
+        conn.change_property32(PropMode::REPLACE, window, net_wm_pid, AtomEnum::CARDINAL, &[pid]).unwrap();
+        let (net_wm_name, utf8) = (atom(conn, "_NET_WM_NAME"), atom(conn, "UTF8_STRING"));
+        conn.change_property8(PropMode::REPLACE, window, net_wm_name, utf8, title.as_bytes()).unwrap();
+        conn.map_window(window).unwrap();
+        conn.sync().unwrap();
+        window
+    }
+
+    /// A PID no real process has, so stray windows can't collide with the test's.
+    fn fake_pid(offset: u32) -> u32 {
+        4_000_000_000 - std::process::id() - offset
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn enumerate_attributes_window_to_its_pid() {
+        let (conn, screen) = connect();
+        let pid = fake_pid(0);
+        let window = create_window(&conn, screen, pid, "aTools test");
+
+        let windows = enumerate_windows().unwrap();
+        let found = windows.iter().find(|w| w.handle == format_handle(window as usize)).unwrap();
+        assert_eq!(found.pid, pid);
+        assert_eq!(found.title, "aTools test");
+        assert_eq!((found.width, found.height), (200, 100));
+    }
+
+    #[test]
+    #[ignore = "needs an X server"]
+    fn worker_reports_main_window_per_pid() {
+        let (conn, screen) = connect();
+        let (first, second) = (fake_pid(1), fake_pid(2));
+        let first_window = create_window(&conn, screen, first, "first");
+        let second_window = create_window(&conn, screen, second, "second");
+
+        // Twice, so the second request goes through the cached connection
+        for _ in 0..2 {
+            let handles = request_main_window_handles().wait();
+            assert_eq!(handles.get(&first), Some(&format_handle(first_window as usize)));
+            assert_eq!(handles.get(&second), Some(&format_handle(second_window as usize)));
+        }
+    }
+}
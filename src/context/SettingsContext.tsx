@@ -34,7 +34,7 @@ const translations: Record<Language, Record<string, string>> = {
         // Process Viewer
         "process.title": "System Processes",
         "process.monitoring": "MONITORING {0} ACTIVE PROCESSES",
-        "process.search_placeholder": "SEARCH PID / NAME...",
+        "process.search_placeholder": "SEARCH PID / NAME, OR name~\"node\" and memory>500MB...",
         "process.pid": "PID",
         "process.ppid": "PPID",
         "process.name": "Name",
@@ -111,6 +111,18 @@ const translations: Record<Language, Record<string, string>> = {
         "automation.placeholder_name": "My Daily Backup",
         "automation.placeholder_cron": "0 30 9 * * * (At 09:30:00am or @daily)",
         "automation.placeholder_cmd": "echo 'Hello World' > log.txt",
+        "automation.misfire": "Missed Runs",
+        "automation.misfire_skip": "Skip",
+        "automation.misfire_run_once": "Run once on wake",
+        "automation.misfire_run_all": "Run every missed run",
+        "automation.misfire_max_runs": "At most this many catch-up runs",
+        "automation.history": "Run History",
+        "automation.output_encoding": "Output Encoding",
+        "automation.timeout": "Timeout (s)",
+        "automation.grace": "Grace Period (s)",
+        "automation.grace_unsupported": "Not available on Windows: timed-out tasks are killed immediately",
+        "automation.placeholder_timeout": "No timeout",
+        "automation.no_runs": "No runs recorded yet.",
     },
     zh: {
         "nav.processes": "系统进程",
@@ -132,7 +144,7 @@ const translations: Record<Language, Record<string, string>> = {
         // Process Viewer
         "process.title": "系统进程",
         "process.monitoring": "正在监控 {0} 个活跃进程",
-        "process.search_placeholder": "搜索 PID / 进程名...",
+        "process.search_placeholder": "搜索 PID / 进程名，或 name~\"node\" and memory>500MB...",
         "process.pid": "PID",
         "process.ppid": "父进程ID",
         "process.name": "名称",
@@ -209,6 +221,18 @@ const translations: Record<Language, Record<string, string>> = {
         "automation.placeholder_name": "每日备份",
         "automation.placeholder_cron": "0 30 9 * * * (每天 09:30:00)",
         "automation.placeholder_cmd": "echo 'Hello' > log.txt",
+        "automation.misfire": "错过的运行",
+        "automation.misfire_skip": "跳过",
+        "automation.misfire_run_once": "唤醒后补运行一次",
+        "automation.misfire_run_all": "补运行每一次",
+        "automation.misfire_max_runs": "最多补运行次数",
+        "automation.history": "运行历史",
+        "automation.output_encoding": "输出编码",
+        "automation.timeout": "超时 (秒)",
+        "automation.grace": "宽限时间 (秒)",
+        "automation.grace_unsupported": "Windows 不支持：任务超时后会被立即终止",
+        "automation.placeholder_timeout": "不限时",
+        "automation.no_runs": "暂无运行记录",
     }
 };
 
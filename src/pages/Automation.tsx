@@ -1,10 +1,15 @@
 import { useState, useEffect, useRef } from "react";
-import { Bot, Plus, Play, Square, Trash2, Edit, Save, X, Clock, Terminal } from "lucide-react";
+import { Bot, Plus, Play, Square, Trash2, Edit, Save, X, Clock, Terminal, History } from "lucide-react";
 import { cn } from "../lib/utils";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useSettings } from "../context/SettingsContext";
 
+type MisfirePolicy =
+    | { kind: "skip" }
+    | { kind: "run_once" }
+    | { kind: "run_all"; max_runs: number };
+
 interface Task {
     id: string;
     name: string;
@@ -13,14 +18,108 @@ interface Task {
     enabled: boolean;
     last_run: string | null;
     last_result: string | null;
+    misfire_policy?: MisfirePolicy;
+    output_encoding?: string;
+    timeout_secs?: number | null;
+    grace_secs?: number;
+}
+
+// Windowless tasks can't be asked to exit on Windows, so a timeout kills them outright
+const GRACE_SUPPORTED = !navigator.userAgent.includes("Windows");
+
+const OUTPUT_ENCODINGS = ["auto", "utf-8", "gbk", "gb18030", "big5", "shift_jis", "euc-kr", "utf-16le", "utf-16be", "windows-1252"];
+
+interface RunRecord {
+    run_id: string;
+    task_id: string;
+    trigger: "cron" | "manual" | "catch_up" | "watch";
+    started_at: string;
+    ended_at: string | null;
+    duration_ms: number | null;
+    exit_code: number | null;
+    status: "running" | "success" | "failed" | "error" | "timed_out" | "interrupted";
+}
+
+interface RunPage {
+    total: number;
+    runs: RunRecord[];
 }
 
+interface TaskOutput {
+    run_id: string;
+    task_id: string;
+    stream: "stdout" | "stderr";
+    ts: number;
+    text: string;
+}
+
+const RUNS_PAGE_SIZE = 10;
+
+const isErrorResult = (result: string) => result.startsWith("Error") || result.startsWith("Timed out");
+
+// Lines with their time since the first line; stderr in red
+const OutputLines = ({ lines }: { lines: TaskOutput[] }) => {
+    const start = lines.length > 0 ? lines[0].ts : 0;
+    return (
+        <>
+            {lines.map((line, i) => (
+                <div key={i} className="flex gap-3">
+                    <span className="shrink-0 w-16 text-right text-muted-foreground select-none">
+                        +{((line.ts - start) / 1000).toFixed(1)}s
+                    </span>
+                    <span className={line.stream === "stderr" ? "text-red-400" : undefined}>{line.text}</span>
+                </div>
+            ))}
+        </>
+    );
+};
+
 const Automation = () => {
     const { t } = useSettings();
     const [tasks, setTasks] = useState<Task[]>([]);
     const [isEditing, setIsEditing] = useState(false);
     const [currentTask, setCurrentTask] = useState<Partial<Task>>({});
-    const [outputStreams, setOutputStreams] = useState<Record<string, string>>({});
+    const [outputStreams, setOutputStreams] = useState<Record<string, TaskOutput[]>>({});
+    const [historyTaskId, setHistoryTaskId] = useState<string | null>(null);
+    const [runPage, setRunPage] = useState<RunPage>({ total: 0, runs: [] });
+    const [runOffset, setRunOffset] = useState(0);
+    const [runOutput, setRunOutput] = useState<{ runId: string; lines: TaskOutput[]; error?: string } | null>(null);
+    // Read from the event listener, which is registered once
+    const historyTaskIdRef = useRef<string | null>(null);
+    historyTaskIdRef.current = historyTaskId;
+
+    const fetchRuns = async (taskId: string, offset: number) => {
+        try {
+            const res = await invoke<RunPage>("get_task_runs", { taskId, paging: { offset, limit: RUNS_PAGE_SIZE } });
+            setRunPage(res);
+            setRunOffset(offset);
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
+    const toggleHistory = (taskId: string) => {
+        setRunOutput(null);
+        if (historyTaskId === taskId) {
+            setHistoryTaskId(null);
+            return;
+        }
+        setHistoryTaskId(taskId);
+        fetchRuns(taskId, 0);
+    };
+
+    const showRunOutput = async (runId: string) => {
+        if (runOutput?.runId === runId) {
+            setRunOutput(null);
+            return;
+        }
+        try {
+            const lines = await invoke<TaskOutput[]>("get_run_output", { runId });
+            setRunOutput({ runId, lines });
+        } catch (e) {
+            setRunOutput({ runId, lines: [], error: String(e) });
+        }
+    };
 
     const fetchTasks = async () => {
         try {
@@ -35,14 +134,17 @@ const Automation = () => {
         fetchTasks();
         const unlisten = listen("task-updated", () => {
             fetchTasks();
+            if (historyTaskIdRef.current) fetchRuns(historyTaskIdRef.current, 0);
         });
         
-        const unlistenOutput = listen<[string, string]>("task-output", (event) => {
-            const [id, msg] = event.payload;
-            setOutputStreams(prev => ({
-                ...prev,
-                [id]: (prev[id] || "") + msg
-            }));
+        const unlistenOutput = listen<TaskOutput>("task-output", (event) => {
+            const line = event.payload;
+            setOutputStreams(prev => {
+                const current = prev[line.task_id] || [];
+                // A new run starts a fresh log
+                const sameRun = current.length > 0 && current[0].run_id === line.run_id;
+                return { ...prev, [line.task_id]: sameRun ? [...current, line] : [line] };
+            });
         });
 
         return () => {
@@ -55,19 +157,30 @@ const Automation = () => {
         if (!currentTask.name || !currentTask.command) return;
         
         const taskPayload = {
+            ...currentTask,
             id: currentTask.id || "",
             name: currentTask.name,
             cron_expr: currentTask.cron_expr || "",
             command: currentTask.command,
             enabled: currentTask.enabled ?? true,
             last_run: currentTask.last_run || null,
-            last_result: currentTask.last_result || null
+            last_result: currentTask.last_result || null,
+            misfire_policy: currentTask.misfire_policy || { kind: "skip" },
+            output_encoding: currentTask.output_encoding || "auto",
+            timeout_secs: currentTask.timeout_secs || null,
+            grace_secs: currentTask.grace_secs ?? 10
         };
 
-        if (currentTask.id) {
-            await invoke("update_task", { task: taskPayload });
-        } else {
-            await invoke("add_task", { task: taskPayload });
+        try {
+            if (currentTask.id) {
+                await invoke("update_task", { task: taskPayload });
+            } else {
+                await invoke("add_task", { task: taskPayload });
+            }
+        } catch (e) {
+            // e.g. resource limits on a platform that can't apply them
+            alert(String(e));
+            return;
         }
         setIsEditing(false);
         setCurrentTask({});
@@ -83,7 +196,7 @@ const Automation = () => {
 
     const handleRun = async (id: string) => {
         // Clear previous output log for this task
-        setOutputStreams(prev => ({ ...prev, [id]: "" }));
+        setOutputStreams(prev => ({ ...prev, [id]: [] }));
         await invoke("run_task_manual", { id });
     };
 
@@ -97,7 +210,7 @@ const Automation = () => {
     };
 
     const startNew = () => {
-        setCurrentTask({ enabled: true, cron_expr: "", command: "", name: "" });
+        setCurrentTask({ enabled: true, cron_expr: "", command: "", name: "", misfire_policy: { kind: "skip" }, output_encoding: "auto", timeout_secs: null, grace_secs: 10 });
         setIsEditing(true);
     };
 
@@ -145,6 +258,36 @@ const Automation = () => {
                                 className="bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none"
                             />
                         </div>
+                        <div className="grid gap-2">
+                            <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.misfire")}</label>
+                            <div className="flex gap-2">
+                                <select
+                                    value={currentTask.misfire_policy?.kind || "skip"}
+                                    onChange={e => {
+                                        const kind = e.target.value;
+                                        const misfire_policy: MisfirePolicy = kind === "run_all"
+                                            ? { kind: "run_all", max_runs: 5 }
+                                            : { kind: kind as "skip" | "run_once" };
+                                        setCurrentTask({...currentTask, misfire_policy});
+                                    }}
+                                    className="flex-1 bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none"
+                                >
+                                    <option value="skip">{t("automation.misfire_skip")}</option>
+                                    <option value="run_once">{t("automation.misfire_run_once")}</option>
+                                    <option value="run_all">{t("automation.misfire_run_all")}</option>
+                                </select>
+                                {currentTask.misfire_policy?.kind === "run_all" && (
+                                    <input
+                                        type="number"
+                                        min={1}
+                                        value={currentTask.misfire_policy.max_runs}
+                                        onChange={e => setCurrentTask({...currentTask, misfire_policy: { kind: "run_all", max_runs: Math.max(1, parseInt(e.target.value) || 1) }})}
+                                        title={t("automation.misfire_max_runs")}
+                                        className="w-24 bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none"
+                                    />
+                                )}
+                            </div>
+                        </div>
                         <div className="grid gap-2">
                             <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.command")} (CMD/Sh)</label>
                             <textarea 
@@ -154,6 +297,43 @@ const Automation = () => {
                                 className="bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none min-h-[100px]"
                             />
                         </div>
+                        <div className="grid gap-2">
+                            <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.output_encoding")}</label>
+                            <select
+                                value={currentTask.output_encoding || "auto"}
+                                onChange={e => setCurrentTask({...currentTask, output_encoding: e.target.value})}
+                                className="bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none"
+                            >
+                                {OUTPUT_ENCODINGS.map(encoding => (
+                                    <option key={encoding} value={encoding}>{encoding}</option>
+                                ))}
+                            </select>
+                        </div>
+                        <div className="grid grid-cols-2 gap-4">
+                            <div className="grid gap-2">
+                                <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.timeout")}</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    value={currentTask.timeout_secs ?? ""}
+                                    onChange={e => setCurrentTask({...currentTask, timeout_secs: e.target.value ? Math.max(0, parseInt(e.target.value) || 0) : null})}
+                                    placeholder={t("automation.placeholder_timeout")}
+                                    className="bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none"
+                                />
+                            </div>
+                            <div className="grid gap-2">
+                                <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.grace")}</label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    value={currentTask.grace_secs ?? 10}
+                                    onChange={e => setCurrentTask({...currentTask, grace_secs: Math.max(0, parseInt(e.target.value) || 0)})}
+                                    disabled={!GRACE_SUPPORTED || !currentTask.timeout_secs}
+                                    title={GRACE_SUPPORTED ? undefined : t("automation.grace_unsupported")}
+                                    className="bg-input border border-border rounded px-4 py-2 font-mono text-sm focus:border-primary focus:outline-none disabled:opacity-50"
+                                />
+                            </div>
+                        </div>
                         <div className="flex items-center gap-2">
                             <label className="text-xs uppercase text-muted-foreground font-mono">{t("automation.enabled")}</label>
                              <button
@@ -200,7 +380,7 @@ const Automation = () => {
                     
                     {tasks.map(task => (
                         <div key={task.id} className={cn(
-                            "group bg-card border border-border rounded p-4 flex flex-col md:flex-row gap-4 justify-between items-start md:items-center hover:border-primary/50 transition-colors",
+                            "group bg-card border border-border rounded p-4 flex flex-col md:flex-row md:flex-wrap gap-4 justify-between items-start md:items-center hover:border-primary/50 transition-colors",
                             !task.enabled && "opacity-60"
                         )}>
                             <div className="flex-1 space-y-2 w-full">
@@ -220,8 +400,8 @@ const Automation = () => {
                                     </div>
                                     {task.last_result && task.last_result !== "Running..."  && task.last_result !== "Success" && (
                                          <details className="w-full">
-                                            <summary className={cn("cursor-pointer hover:underline flex items-center gap-1", task.last_result.startsWith("Error") ? "text-destructive" : "text-foreground")}>
-                                              <span>[{task.last_result.startsWith("Error") ? "ERR" : "OK"}] Output</span>
+                                            <summary className={cn("cursor-pointer hover:underline flex items-center gap-1", isErrorResult(task.last_result) ? "text-destructive" : "text-foreground")}>
+                                              <span>[{isErrorResult(task.last_result) ? "ERR" : "OK"}] Output</span>
                                             </summary>
                                             <div className="mt-2 p-2 bg-black/50 rounded overflow-x-auto whitespace-pre-wrap max-h-40 border border-border">
                                                 {task.last_result}
@@ -235,7 +415,9 @@ const Automation = () => {
                                         <div className="w-full mt-2">
                                             <div className="text-yellow-500 font-bold animate-pulse text-xs mb-1">[RUNNING...]</div>
                                             <div className="p-2 bg-black/80 rounded overflow-x-auto overflow-y-auto whitespace-pre-wrap h-40 border border-yellow-500/30 text-xs font-mono text-green-400">
-                                                {outputStreams[task.id] || task.last_result || "Waiting for output..."}
+                                                {outputStreams[task.id]?.length
+                                                    ? <OutputLines lines={outputStreams[task.id]} />
+                                                    : (task.last_result || "Waiting for output...")}
                                                 <div ref={(el) => el?.scrollIntoView({ behavior: "smooth" })} />
                                             </div>
                                         </div>
@@ -261,6 +443,16 @@ const Automation = () => {
                                         <Play className="h-4 w-4" />
                                     </button>
                                 )}
+                                <button 
+                                    onClick={() => toggleHistory(task.id)}
+                                    className={cn(
+                                        "p-2 rounded transition-colors",
+                                        historyTaskId === task.id ? "bg-primary text-black" : "bg-muted text-muted-foreground hover:bg-muted-foreground hover:text-black"
+                                    )}
+                                    title={t("automation.history")}
+                                >
+                                    <History className="h-4 w-4" />
+                                </button>
                                 <button 
                                     onClick={() => startEdit(task)}
                                     className="p-2 bg-muted text-muted-foreground rounded hover:bg-muted-foreground hover:text-black transition-colors"
@@ -276,6 +468,55 @@ const Automation = () => {
                                     <Trash2 className="h-4 w-4" />
                                 </button>
                             </div>
+
+                            {historyTaskId === task.id && (
+                                <div className="w-full basis-full text-xs font-mono space-y-1">
+                                    {runPage.runs.length === 0 && (
+                                        <div className="text-muted-foreground">{t("automation.no_runs")}</div>
+                                    )}
+                                    {runPage.runs.map(run => (
+                                        <div key={run.run_id}>
+                                            <button
+                                                onClick={() => showRunOutput(run.run_id)}
+                                                className="w-full grid grid-cols-[1fr_6rem_6rem_6rem] gap-2 text-left px-2 py-1 rounded hover:bg-muted/50"
+                                            >
+                                                <span>{new Date(run.started_at).toLocaleString()}</span>
+                                                <span className="text-muted-foreground">{run.trigger}</span>
+                                                <span className="text-muted-foreground">{run.duration_ms !== null ? `${(run.duration_ms / 1000).toFixed(1)}s` : "-"}</span>
+                                                <span className={cn(
+                                                    run.status === "success" && "text-green-500",
+                                                    (run.status === "failed" || run.status === "error" || run.status === "timed_out") && "text-destructive",
+                                                    run.status === "running" && "text-yellow-500"
+                                                )}>
+                                                    {run.status}{run.exit_code !== null && run.status !== "success" ? ` (${run.exit_code})` : ""}
+                                                </span>
+                                            </button>
+                                            {runOutput?.runId === run.run_id && (
+                                                <div className="mt-1 p-2 bg-black/50 rounded overflow-auto whitespace-pre-wrap max-h-60 border border-border">
+                                                    {runOutput.error
+                                                        ? <span className="text-destructive">{runOutput.error}</span>
+                                                        : runOutput.lines.length > 0 ? <OutputLines lines={runOutput.lines} /> : "-"}
+                                                </div>
+                                            )}
+                                        </div>
+                                    ))}
+                                    {runPage.total > RUNS_PAGE_SIZE && (
+                                        <div className="flex items-center gap-4 pt-1 text-muted-foreground">
+                                            <button
+                                                disabled={runOffset === 0}
+                                                onClick={() => fetchRuns(task.id, Math.max(0, runOffset - RUNS_PAGE_SIZE))}
+                                                className="hover:text-primary disabled:opacity-30"
+                                            >&lt;</button>
+                                            <span>{runOffset + 1}-{Math.min(runOffset + RUNS_PAGE_SIZE, runPage.total)} / {runPage.total}</span>
+                                            <button
+                                                disabled={runOffset + RUNS_PAGE_SIZE >= runPage.total}
+                                                onClick={() => fetchRuns(task.id, runOffset + RUNS_PAGE_SIZE)}
+                                                className="hover:text-primary disabled:opacity-30"
+                                            >&gt;</button>
+                                        </div>
+                                    )}
+                                </div>
+                            )}
                         </div>
                     ))}
                 </div>
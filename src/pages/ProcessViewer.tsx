@@ -3,6 +3,13 @@ import { invoke } from "@tauri-apps/api/core";
 import { RefreshCw, Search,  Activity, HardDrive } from "lucide-react";
 import { useSettings } from "../context/SettingsContext";
 
+interface SocketInfo {
+    proto: "tcp" | "udp";
+    local_addr: string;
+    remote_addr?: string;
+    state?: string;
+}
+
 interface ProcessInfo {
     pid: number;
     ppid?: number;
@@ -12,10 +19,23 @@ interface ProcessInfo {
     memory: number;
     cpu_usage: number;
     status: string;
+    user?: string;
     ports: number[];
+    sockets: SocketInfo[];
     window_handle?: string;
 }
 
+interface ProcessPage {
+    total: number;
+    processes: ProcessInfo[];
+}
+
+interface QueryError {
+    message: string;
+    start: number;
+    end: number;
+}
+
 const formatBytes = (bytes: number, decimals = 2) => {
     if (!+bytes) return '0 B';
     const k = 1024;
@@ -25,10 +45,16 @@ const formatBytes = (bytes: number, decimals = 2) => {
     return `${parseFloat((bytes / Math.pow(k, i)).toFixed(dm))} ${sizes[i]}`;
 };
 
+const FILTER_DEBOUNCE_MS = 300;
+
 const ProcessViewer = () => {
     const [processes, setProcesses] = useState<ProcessInfo[]>([]);
+    const [total, setTotal] = useState(0);
     const [loading, setLoading] = useState(false);
     const [filter, setFilter] = useState("");
+    // What is actually queried; trails `filter` so typing doesn't hit get_processes per keystroke
+    const [debouncedFilter, setDebouncedFilter] = useState("");
+    const [queryError, setQueryError] = useState<QueryError | null>(null);
     const [sortBy, setSortBy] = useState<keyof ProcessInfo>("cpu_usage");
     const [sortDesc, setSortDesc] = useState(true);
     const { t } = useSettings();
@@ -36,33 +62,34 @@ const ProcessViewer = () => {
     const fetchProcesses = async () => {
         setLoading(true);
         try {
-            const res = await invoke<ProcessInfo[]>("get_processes");
-            setProcesses(res);
+            // Filtering and sorting happen in Rust, see src-tauri/src/query.rs
+            const res = await invoke<ProcessPage>("get_processes", {
+                query: { filter: debouncedFilter, sort_by: sortBy, descending: sortDesc },
+            });
+            setProcesses(res.processes);
+            setTotal(res.total);
+            setQueryError(null);
         } catch (e) {
-            console.error("Failed to fetch processes", e);
+            if (e && typeof e === "object" && "start" in e) {
+                setQueryError(e as QueryError);
+            } else {
+                console.error("Failed to fetch processes", e);
+            }
         } finally {
             setLoading(false);
         }
     };
 
+    useEffect(() => {
+        const timeout = setTimeout(() => setDebouncedFilter(filter), FILTER_DEBOUNCE_MS);
+        return () => clearTimeout(timeout);
+    }, [filter]);
+
     useEffect(() => {
         fetchProcesses();
         const interval = setInterval(fetchProcesses, 5000); // Auto refresh every 5s
         return () => clearInterval(interval);
-    }, []);
-
-    const filteredProcesses = processes.filter(p => 
-        p.name.toLowerCase().includes(filter.toLowerCase()) || 
-        p.pid.toString().includes(filter)
-    ).sort((a, b) => {
-        const valA = a[sortBy];
-        const valB = b[sortBy];
-        if (valA === undefined || valB === undefined) return 0;
-        
-        if (valA < valB) return sortDesc ? 1 : -1;
-        if (valA > valB) return sortDesc ? -1 : 1;
-        return 0;
-    });
+    }, [debouncedFilter, sortBy, sortDesc]);
 
     const handleSort = (key: keyof ProcessInfo) => {
         if (sortBy === key) {
@@ -82,7 +109,7 @@ const ProcessViewer = () => {
                         {t("process.title")}
                     </h2>
                     <p className="text-muted-foreground text-xs font-mono mt-1">
-                        {t("process.monitoring").replace("{0}", processes.length.toString())}
+                        {t("process.monitoring").replace("{0}", total.toString())}
                     </p>
                 </div>
 
@@ -94,8 +121,16 @@ const ProcessViewer = () => {
                             placeholder={t("process.search_placeholder")} 
                             value={filter}
                             onChange={(e) => setFilter(e.target.value)}
-                            className="w-full bg-input border border-border rounded pl-9 pr-4 py-2 text-sm focus:outline-none focus:border-primary text-foreground placeholder:text-muted-foreground font-mono"
+                            className={`w-full bg-input border rounded pl-9 pr-4 py-2 text-sm focus:outline-none text-foreground placeholder:text-muted-foreground font-mono ${
+                                queryError ? "border-red-500 focus:border-red-500" : "border-border focus:border-primary"
+                            }`}
+                            title={queryError ? `${queryError.message} (${queryError.start}-${queryError.end})` : undefined}
                         />
+                        {queryError && (
+                            <p className="absolute left-0 top-full mt-1 text-[10px] text-red-500 font-mono">
+                                {queryError.message} @ {queryError.start}
+                            </p>
+                        )}
                     </div>
                     <button 
                         onClick={fetchProcesses}
@@ -125,7 +160,7 @@ const ProcessViewer = () => {
                             </tr>
                         </thead>
                         <tbody className="divide-y divide-border">
-                            {filteredProcesses.map((p) => (
+                            {processes.map((p) => (
                                 <tr key={p.pid} className="hover:bg-primary/5 transition-colors group">
                                     <td className="p-4 text-muted-foreground group-hover:text-foreground">{p.pid}</td>
                                     <td className="p-4 text-muted-foreground">{p.ppid || '-'}</td>